@@ -1,45 +1,139 @@
-use std::collections::HashMap;
+use std::path::PathBuf;
 
-use graphics::types::Color;
-use opengl_graphics::GlGraphics;
-use piston::input::Button::Keyboard;
-use piston::input::{ButtonArgs, ButtonState, Key, RenderArgs, UpdateArgs};
+use piston::input::Button::{Keyboard, Mouse};
+use piston::input::{ButtonArgs, ButtonState, Key, MouseButton, UpdateArgs};
 use piston::window;
 
-use maze::generation::{self, BacktrackingCellState};
-use maze::maze::{Cell, Direction, Maze, Point};
-use maze::solving::AStarSolver;
+use maze::cli::GuiOptions;
+use maze::color::Color;
+use maze::generation::{CellState, Generator, GeneratorKind, PrebuiltGenerator};
+use maze::grid::Grid;
+use maze::history::{History, Operation, Reversible};
+use maze::layout::{CellLayout, Pick};
+use maze::maze::{Direction, Maze, Point};
+use maze::play::Game;
+use maze::race::Race;
+use maze::raster::Canvas;
+use maze::render::{MazeDrawer, Renderer};
+use maze::scene::Scene;
+use maze::session::Session;
+use maze::solving::SolverKind;
+use maze::stats::MazeStats;
+use maze::theme::Theme;
 
-const BACK_COLOR: Color = [0.204, 0.286, 0.369, 1.0];
-// const BACK_COLOR: Color = [0.9, 0.9, 0.9, 1.0];
+const MIN_DELAY: f64 = 0.0001;
+const MAX_DELAY: f64 = 1.0;
+const HISTORY_LIMIT: usize = 1_000_000;
+const MIN_ZOOM: f64 = 0.5;
+const MAX_CELL_SIZE: f64 = 200.0;
+const FONT_SIZE: u32 = 14;
+// Cursor movement, in pixels, after which a press becomes a drag.
+const DRAG_THRESHOLD: f64 = 4.0;
 
-const VISITED_COLOR: Color = [0.0, 0.0, 1.0, 1.0];
-const CURRENT_COLOR: Color = [1.0, 1.0, 0.0, 1.0];
-const END_COLOR: Color = [1.0, 0.0, 0.0, 1.0];
-const START_COLOR: Color = [0.0, 1.0, 0.0, 1.0];
-const WALL_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
-const PATH_COLOR: Color = [0.0, 48.0, 78.0, 1.0];
+#[derive(Clone, Copy)]
+enum Change {
+    Maze(Operation),
+    Color {
+        point: Point,
+        from: Option<Color>,
+        to: Option<Color>,
+    },
+}
+
+impl Reversible for Change {
+    fn reversed(&self) -> Self {
+        match *self {
+            Change::Maze(operation) => Change::Maze(operation.reversed()),
+            Change::Color { point, from, to } => Change::Color {
+                point,
+                from: to,
+                to: from,
+            },
+        }
+    }
+}
+
+// Changes made by one generation step or one edit.
+struct Record {
+    generated: bool,
+    changes: Vec<Change>,
+}
 
-type ColorMap = HashMap<Point, Option<Color>>;
+impl Reversible for Record {
+    fn reversed(&self) -> Self {
+        Self {
+            generated: self.generated,
+            changes: self.changes.reversed(),
+        }
+    }
+}
+
+// Every solver running on the maze, each with its own pane.
+struct RaceView {
+    race: Race,
+    scenes: Vec<Scene>,
+}
+
+// Every generator building a maze of the same size from the same seed, each with its own pane.
+struct ComparisonView {
+    seed: u64,
+    panes: Vec<GeneratorPane>,
+}
+
+impl ComparisonView {
+    fn is_done(&self) -> bool {
+        self.panes.iter().all(|pane| pane.generator.is_done())
+    }
+}
+
+struct GeneratorPane {
+    generator: Box<dyn Generator>,
+    scene: Scene,
+    steps: usize,
+    // Computed once the maze is done.
+    stats: Option<MazeStats>,
+}
 
 struct MazeInfo {
-    #[allow(dead_code)]
     width: usize,
-    #[allow(dead_code)]
     height: usize,
+    grid: Grid,
     start: Point,
     end: Point,
 }
 
 pub struct App {
-    pub gl: GlGraphics,
     // App Space
     resolution: window::Size,
     // Maze
-    maze_generator: generation::BacktrackingGenerator,
+    maze_generator: Box<dyn Generator>,
+    generator_kind: GeneratorKind,
+    solver_kind: SolverKind,
+    seed: Option<u64>,
     maze_drawer: MazeDrawer,
+    theme: Theme,
     maze_info: MazeInfo,
-    color_map: HashMap<Point, Option<Color>>,
+    scene: Scene,
+    show_path: bool,
+    cursor: [f64; 2],
+    // Camera
+    zoom: f64,
+    pressed: Option<MouseButton>,
+    drag_distance: f64,
+    history: History<Record>,
+    // Generation steps waiting to be redone.
+    undone_steps: usize,
+    steps: usize,
+    elapsed: f64,
+    // Length of the last solution and number of nodes expanded to find it.
+    solution: Option<(Option<usize>, usize)>,
+    // HUD
+    show_hud: bool,
+    message: Option<String>,
+    game: Option<Game>,
+    race: Option<RaceView>,
+    comparison: Option<ComparisonView>,
+    session_file: PathBuf,
     // Simulation
     delay_between_steps: f64,
     timer: f64,
@@ -47,9 +141,17 @@ pub struct App {
 }
 
 impl App {
-    fn cell_size(&self) -> f64 {
-        let cell_size_x = self.resolution.width as f64 / self.maze_generator.width as f64;
-        let cell_size_y = self.resolution.height as f64 / self.maze_generator.height as f64;
+    // Size of the cells when the whole maze fits in the window.
+    fn fit_cell_size(&self) -> f64 {
+        let MazeInfo {
+            width,
+            height,
+            grid,
+            ..
+        } = self.maze_info;
+        let [width, height] = grid.extent(width, height);
+        let cell_size_x = self.resolution.width / width;
+        let cell_size_y = self.resolution.height / height;
         if cell_size_x < cell_size_y {
             cell_size_x
         } else {
@@ -57,255 +159,1103 @@ impl App {
         }
     }
 
+    fn cell_size(&self) -> f64 {
+        self.fit_cell_size() * self.zoom
+    }
+
+    fn reset_view(&mut self) {
+        self.zoom = 1.0;
+        self.maze_drawer.layout = CellLayout::with_grid(self.maze_info.grid, self.fit_cell_size());
+    }
+
+    // Zooms around the cursor, keeping the point below it in place.
+    pub fn mouse_scroll(&mut self, scroll: [f64; 2]) {
+        let max_zoom = (MAX_CELL_SIZE / self.fit_cell_size()).max(1.0);
+        self.zoom = (self.zoom * 1.2_f64.powf(scroll[1])).clamp(MIN_ZOOM, max_zoom);
+
+        let cell_size = self.cell_size();
+        let [x, y] = self.cursor;
+        let layout = &mut self.maze_drawer.layout;
+        let ratio = cell_size / layout.cell_size;
+        layout.x_offset = x - (x - layout.x_offset) * ratio;
+        layout.y_offset = y - (y - layout.y_offset) * ratio;
+        layout.cell_size = cell_size;
+    }
+
+    // Centers the view on a cell that went out of sight.
+    fn follow(&mut self, point: &Point) {
+        let (width, height) = (self.resolution.width, self.resolution.height);
+        let layout = &mut self.maze_drawer.layout;
+        let MazeInfo {
+            width: columns,
+            height: rows,
+            ..
+        } = self.maze_info;
+        let [x, y, size, _] = layout.cell_rectangle(point, columns, rows);
+        if x < 0.0 || y < 0.0 || x + size > width || y + size > height {
+            layout.x_offset += (width - size) / 2.0 - x;
+            layout.y_offset += (height - size) / 2.0 - y;
+        }
+    }
+
     fn clear_color_map(&mut self) {
-        let start = &self.maze_info.start;
-        let end = &self.maze_info.end;
-
-        self.color_map
-            .iter_mut()
-            .filter(|&(point, _)| point != start && point != end)
-            .for_each(|(_, color)| *color = None);
-    }
-
-    pub fn new(gl: GlGraphics, resolution: window::Size) -> Self {
-        let width = 30;
-        let height = 20;
-        let start = Point { x: 0, y: 0 };
-        let end = Point {
-            x: width - 1,
-            y: height - 1,
-        };
+        let MazeInfo { start, end, .. } = self.maze_info;
 
-        let maze_info = MazeInfo {
+        self.scene.map_colors(|point, color| {
+            if point == start || point == end {
+                color
+            } else {
+                None
+            }
+        });
+    }
+
+    fn clear_path(&mut self) {
+        let theme = self.theme;
+        self.scene.map_colors(|_, color| {
+            if color == Some(theme.path) {
+                Some(theme.visited)
+            } else {
+                color
+            }
+        });
+    }
+
+    fn restart(&mut self) {
+        let MazeInfo {
             width,
             height,
+            grid,
             start,
             end,
+        } = self.maze_info;
+        // A loaded maze cannot be generated again, its generator starts over instead.
+        if self.maze_generator.name() == PrebuiltGenerator::NAME {
+            self.maze_generator = self
+                .generator_kind
+                .build_with_grid(grid, width, height, self.seed);
+        } else {
+            self.maze_generator.restart();
+        }
+        let maze = self.maze_generator.maze_mut();
+        maze.set_start(start.x, start.y);
+        maze.set_end(end.x, end.y);
+        self.reset();
+        self.clear_color_map();
+    }
+
+    fn reset(&mut self) {
+        self.timer = 0.0;
+        self.show_path = false;
+        self.history.clear();
+        self.undone_steps = 0;
+        self.steps = 0;
+        self.elapsed = 0.0;
+        self.solution = None;
+        self.game = None;
+        self.scene.walls_changed();
+    }
+
+    fn session(&self) -> Session {
+        let colors = self
+            .maze_generator
+            .maze()
+            .points()
+            .into_iter()
+            .filter_map(|point| self.scene.color(&point).map(|color| (point, color)))
+            .collect();
+        Session {
+            maze: self.maze_generator.maze().clone(),
+            generator: self.generator_kind,
+            seed: self.seed,
+            colors,
+        }
+    }
+
+    fn save_session(&mut self) {
+        let file = self.session_file.display();
+        self.message = Some(match self.session().save(&self.session_file) {
+            Ok(()) => format!("saved to {}", file),
+            Err(error) => format!("cannot save {}: {}", file, error),
+        });
+    }
+
+    fn load_session(&mut self) {
+        let file = self.session_file.display().to_string();
+        match Session::load(&self.session_file) {
+            Ok(session) => {
+                self.open_session(session);
+                self.message = Some(format!("loaded {}", file));
+            }
+            Err(error) => self.message = Some(format!("cannot load {}: {}", file, error)),
+        }
+    }
+
+    fn open_session(&mut self, session: Session) {
+        let maze = session.maze;
+        let (width, height) = (maze.width, maze.height);
+        self.maze_info = MazeInfo {
+            width,
+            height,
+            grid: maze.grid(),
+            start: maze.start(),
+            end: maze.end(),
         };
+        self.generator_kind = session.generator;
+        self.seed = session.seed;
+        self.maze_generator = Box::new(PrebuiltGenerator::new(maze));
 
-        let maze_generator = generation::BacktrackingGenerator::new(width, height);
-        let maze_drawer = MazeDrawer::new();
-
-        let mut color_map = ColorMap::with_capacity(width * height);
-        for x in 0..width {
-            for y in 0..height {
-                let point = Point { x, y };
-                if point == maze_generator.get_maze_ref().start() {
-                    color_map.insert(point, Some(START_COLOR));
-                } else if point == maze_generator.get_maze_ref().end() {
-                    color_map.insert(point, Some(END_COLOR));
-                } else {
-                    color_map.insert(point, None);
+        self.scene = Scene::with_grid(self.maze_info.grid, width, height);
+        for (point, color) in session.colors {
+            self.scene.set_color(&point, Some(color));
+        }
+        self.reset();
+        self.reset_view();
+    }
+
+    // Redrawn by the software renderer rather than read back from the window: it has no
+    // fonts, so the HUD and messages are left out.
+    fn screenshot(&mut self) {
+        let file = (1..)
+            .map(|i| PathBuf::from(format!("maze-screenshot-{}.png", i)))
+            .find(|file| !file.exists())
+            .unwrap();
+        let mut canvas = Canvas::new(
+            self.resolution.width as usize,
+            self.resolution.height as usize,
+        );
+        let show_hud = std::mem::replace(&mut self.show_hud, false);
+        self.render(&mut canvas);
+        self.show_hud = show_hud;
+
+        self.message = Some(match canvas.save_png(&file) {
+            Ok(()) => format!("screenshot saved to {} (without the HUD)", file.display()),
+            Err(error) => format!("cannot save {}: {}", file.display(), error),
+        });
+    }
+
+    fn is_generating(&self) -> bool {
+        !self.maze_generator.is_done() || self.undone_steps > 0
+    }
+
+    fn apply(&mut self, changes: &[Change]) {
+        for change in changes.iter() {
+            match *change {
+                Change::Maze(operation) => {
+                    operation.apply(self.maze_generator.maze_mut());
+                    self.wall_changed(&operation);
+                }
+                Change::Color { point, to, .. } => {
+                    self.scene.set_color(&point, to);
                 }
             }
         }
+        let maze = self.maze_generator.maze();
+        self.maze_info.start = maze.start();
+        self.maze_info.end = maze.end();
+    }
 
-        let mut app = Self {
-            gl,
-            resolution,
-            maze_drawer,
-            maze_info,
-            maze_generator,
-            timer: 0.0,
-            delay_between_steps: 0.005,
-            paused: false,
-            color_map,
+    fn wall_changed(&mut self, operation: &Operation) {
+        if let Operation::RemoveWall(p1, p2) | Operation::AddWall(p1, p2) = operation {
+            self.scene.wall_changed(p1, p2);
+        }
+    }
+
+    fn set_color(&mut self, changes: &mut Vec<Change>, point: Point, color: Option<Color>) {
+        let from = self.scene.set_color(&point, color);
+        changes.push(Change::Color {
+            point,
+            from,
+            to: color,
+        });
+    }
+
+    // Replays undone steps before asking the generator for new ones.
+    fn step(&mut self) {
+        if !self.is_generating() {
+            return;
+        }
+        while let Some(record) = self.history.redo() {
+            let generated = record.generated;
+            let changes = record.changes.clone();
+            self.apply(&changes);
+            if generated {
+                self.undone_steps -= 1;
+                self.steps += 1;
+                return;
+            }
+        }
+
+        let step = self.maze_generator.next_step();
+        step.walls.iter().for_each(|op| self.wall_changed(op));
+        let mut changes: Vec<Change> = step.walls.into_iter().map(Change::Maze).collect();
+        for (point, state) in step.cells {
+            if point != self.maze_info.start && point != self.maze_info.end {
+                let color = cell_color(&self.theme, state);
+                self.set_color(&mut changes, point, color);
+            }
+        }
+        // The last step only notices that the generation is done.
+        if !changes.is_empty() {
+            self.steps += 1;
+            self.history.record(Record {
+                generated: true,
+                changes,
+            });
+        }
+    }
+
+    fn complete(&mut self) {
+        while self.is_generating() {
+            self.step();
+        }
+        self.timer = 0.0;
+    }
+
+    fn undo(&mut self) {
+        if let Some(record) = self.history.undo() {
+            self.paused = true;
+            self.apply(&record.changes);
+            if record.generated {
+                self.undone_steps += 1;
+                self.steps -= 1;
+                self.solution = None;
+                self.show_path = false;
+                self.clear_path();
+            } else {
+                self.refresh_path();
+            }
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(record) = self.history.redo() {
+            let generated = record.generated;
+            let changes = record.changes.clone();
+            self.apply(&changes);
+            if generated {
+                self.undone_steps -= 1;
+                self.steps += 1;
+            } else {
+                self.refresh_path();
+            }
+        }
+    }
+
+    // Edits would be lost when undone generation steps are replayed.
+    fn can_edit(&mut self) -> bool {
+        if self.undone_steps > 0 {
+            self.message = Some("redo the undone steps before editing".to_string());
+        }
+        self.undone_steps == 0
+    }
+
+    fn record_edit(&mut self, changes: Vec<Change>) {
+        self.apply(&changes);
+        self.history.record(Record {
+            generated: false,
+            changes,
+        });
+        self.refresh_path();
+    }
+
+    fn solve(&mut self) {
+        let start = self.maze_info.start;
+        let end = self.maze_info.end;
+
+        let (path, nodes_expanded) = {
+            let mut solver = self.solver_kind.build(self.maze_generator.maze());
+            (solver.solve(), solver.nodes_expanded())
         };
-        app.maze_drawer.set_cell_size(app.cell_size());
-        app
+        // The path includes both the start and the end.
+        self.solution = Some((path.as_ref().map(|path| path.len() - 1), nodes_expanded));
+
+        self.show_path = true;
+        self.clear_path();
+        if let Some(path) = path {
+            for node in path.iter().filter(|&p| p != &start && p != &end) {
+                self.scene.set_color(node, Some(self.theme.path));
+            }
+        }
     }
 
-    pub fn render(&mut self, args: &RenderArgs) {
-        use graphics::*;
+    // Keeps a displayed solution in sync with the edits.
+    fn refresh_path(&mut self) {
+        if self.show_path {
+            self.solve();
+        }
+    }
 
-        self.resolution = window::Size::from(args.window_size);
-        let cell_size = self.cell_size();
+    fn move_endpoint(&mut self, point: Point, is_start: bool) {
+        let MazeInfo { start, end, .. } = self.maze_info;
+        if point == start || point == end || !self.can_edit() {
+            return;
+        }
+
+        let (old, color, operation) = if is_start {
+            (
+                start,
+                self.theme.start,
+                Operation::MoveStart {
+                    from: start,
+                    to: point,
+                },
+            )
+        } else {
+            (
+                end,
+                self.theme.end,
+                Operation::MoveEnd {
+                    from: end,
+                    to: point,
+                },
+            )
+        };
 
-        let maze = self.maze_generator.get_maze_ref();
+        let old_color = if self.maze_generator.is_done() {
+            Some(self.theme.visited)
+        } else {
+            None
+        };
+        let changes = vec![
+            Change::Maze(operation),
+            Change::Color {
+                point: old,
+                from: Some(color),
+                to: old_color,
+            },
+            Change::Color {
+                point,
+                from: self.scene.color(&point),
+                to: Some(color),
+            },
+        ];
+        self.record_edit(changes);
+    }
 
-        let maze_drawer = {
-            let maze_drawer = &mut self.maze_drawer;
-            maze_drawer.set_cell_size(cell_size);
-            &self.maze_drawer
+    fn toggle_wall(&mut self, p1: Point, p2: Point) {
+        if !self.can_edit() {
+            return;
+        }
+        let operation = if self.maze_generator.maze().is_wall_present(&p1, &p2) {
+            Operation::RemoveWall(p1, p2)
+        } else {
+            Operation::AddWall(p1, p2)
         };
+        self.record_edit(vec![Change::Maze(operation)]);
+    }
+
+    pub fn mouse_move(&mut self, position: [f64; 2]) {
+        let (dx, dy) = (position[0] - self.cursor[0], position[1] - self.cursor[1]);
+        self.cursor = position;
+
+        if self.pressed.is_some() {
+            self.drag_distance += dx.abs() + dy.abs();
+            if self.drag_distance > DRAG_THRESHOLD {
+                self.maze_drawer.layout.x_offset += dx;
+                self.maze_drawer.layout.y_offset += dy;
+            }
+        }
+    }
+
+    // A press followed by a release without dragging in between is a click.
+    fn mouse_button(&mut self, button: MouseButton, state: ButtonState) {
+        match state {
+            ButtonState::Press => {
+                self.pressed = Some(button);
+                self.drag_distance = 0.0;
+            }
+            ButtonState::Release => {
+                if self.pressed == Some(button) && self.drag_distance <= DRAG_THRESHOLD {
+                    self.click(button);
+                }
+                self.pressed = None;
+            }
+        }
+    }
 
-        let color_map = &self.color_map;
+    fn click(&mut self, button: MouseButton) {
+        if self.game.is_some() || self.race.is_some() || self.comparison.is_some() {
+            return;
+        }
+        let pick = self.maze_drawer.layout.pick(
+            self.cursor[0],
+            self.cursor[1],
+            self.maze_info.width,
+            self.maze_info.height,
+        );
+        match (pick, button) {
+            (Some(Pick::Cell(point)), MouseButton::Left) => self.move_endpoint(point, true),
+            (Some(Pick::Cell(point)), MouseButton::Right) => self.move_endpoint(point, false),
+            // Walls are only editable once the generator is done with the maze.
+            (Some(Pick::Wall(p1, p2)), MouseButton::Left) if self.maze_generator.is_done() => {
+                self.toggle_wall(p1, p2)
+            }
+            _ => {}
+        }
+    }
 
-        self.gl.draw(args.viewport(), |c, gl| {
-            clear(BACK_COLOR, gl);
-            maze_drawer.draw_maze(&c, gl, &maze, &color_map);
+    fn start_game(&mut self) {
+        self.complete();
+        self.clear_color_map();
+        self.game = Some(Game::new(self.maze_generator.maze()));
+    }
+
+    fn toggle_play(&mut self) {
+        if self.game.take().is_none() {
+            self.start_game();
+            return;
+        }
+
+        let MazeInfo { start, end, .. } = self.maze_info;
+        let visited = self.theme.visited;
+        self.scene.map_colors(|point, color| {
+            if point == start || point == end {
+                color
+            } else {
+                Some(visited)
+            }
         });
+        self.refresh_path();
     }
 
-    pub fn update(&mut self, args: &UpdateArgs) {
-        if !self.paused {
-            self.timer += args.dt;
-            if self.timer >= self.delay_between_steps {
-                let number_of_steps = (self.timer / self.delay_between_steps) as i32;
-                for _ in 0..number_of_steps {
-                    self.timer -= self.delay_between_steps;
-                    let modified_cells = self.maze_generator.next_step();
-                    modified_cells.iter().for_each(|(point, state)| {
-                        if point != &self.maze_info.start && point != &self.maze_info.end {
-                            let color = match state {
-                                BacktrackingCellState::Unvisited => None,
-                                BacktrackingCellState::Visited => Some(VISITED_COLOR),
-                                BacktrackingCellState::Current => Some(CURRENT_COLOR),
-                            };
-                            self.color_map.insert(*point, color);
-                        }
-                    });
-                    if self.maze_generator.is_done() {
-                        break;
-                    }
+    fn play_move(&mut self, direction: Direction) {
+        let MazeInfo { start, end, .. } = self.maze_info;
+        let game = match self.game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+
+        let previous = game.position();
+        if game.try_move(self.maze_generator.maze(), direction) {
+            if previous != start && previous != end {
+                self.scene.set_color(&previous, Some(self.theme.trail));
+            }
+            let position = game.position();
+            self.follow(&position);
+        }
+    }
+
+    fn play_input(&mut self, key: Key) {
+        if self.maze_info.grid == Grid::Polar {
+            self.polar_play_input(key);
+            return;
+        }
+        match key {
+            Key::Up => self.play_move(Direction::North),
+            Key::Down => self.play_move(Direction::South),
+            Key::Right => self.play_move(Direction::East),
+            Key::Left => self.play_move(Direction::West),
+            // Around the home row, for the diagonal sides of hex cells.
+            Key::W => self.play_move(Direction::NorthWest),
+            Key::E => self.play_move(Direction::NorthEast),
+            Key::A => self.play_move(Direction::West),
+            Key::D => self.play_move(Direction::East),
+            Key::Z => self.play_move(Direction::SouthWest),
+            Key::X => self.play_move(Direction::SouthEast),
+            Key::R => self.start_game(),
+            Key::Space => self.toggle_play(),
+            _ => {}
+        }
+    }
+
+    // Up and down move across rings, left and right around them.
+    fn polar_play_input(&mut self, key: Key) {
+        match key {
+            Key::Up => {
+                let position = match &self.game {
+                    Some(game) => game.position(),
+                    None => return,
+                };
+                let inward = self
+                    .maze_generator
+                    .maze()
+                    .directions(&position)
+                    .iter()
+                    .find(|direction| {
+                        matches!(
+                            direction,
+                            Direction::Inward
+                                | Direction::InwardClockwise
+                                | Direction::InwardCounterClockwise
+                        )
+                    })
+                    .cloned();
+                if let Some(direction) = inward {
+                    self.play_move(direction);
                 }
             }
+            Key::Down => self.play_move(Direction::Outward),
+            Key::Right => self.play_move(Direction::Clockwise),
+            Key::Left => self.play_move(Direction::CounterClockwise),
+            // Cells whose outer side is split in two.
+            Key::Z => self.play_move(Direction::OutwardCounterClockwise),
+            Key::X => self.play_move(Direction::OutwardClockwise),
+            Key::R => self.start_game(),
+            Key::Space => self.toggle_play(),
+            _ => {}
         }
     }
 
-    pub fn input(&mut self, args: &ButtonArgs) {
-        if let Keyboard(key) = args.button {
-            if let Key::R = key {
-                if args.state == ButtonState::Press {
-                    self.maze_generator.restart();
-                    self.timer = 0.0;
-                    self.clear_color_map();
+    fn start_race(&mut self) {
+        self.complete();
+        let MazeInfo {
+            width,
+            height,
+            grid,
+            start,
+            end,
+        } = self.maze_info;
+
+        let race = Race::new(self.maze_generator.maze(), &SolverKind::ALL);
+        let scenes = race
+            .lanes()
+            .iter()
+            .map(|_| {
+                let mut scene = Scene::with_grid(grid, width, height);
+                scene.set_color(&start, Some(self.theme.start));
+                scene.set_color(&end, Some(self.theme.end));
+                scene
+            })
+            .collect();
+        self.race = Some(RaceView { race, scenes });
+        self.timer = 0.0;
+    }
+
+    fn toggle_race(&mut self) {
+        if self.race.take().is_none() {
+            self.start_race();
+        }
+    }
+
+    fn race_step(&mut self) {
+        let MazeInfo { start, end, .. } = self.maze_info;
+        let theme = self.theme;
+        let view = match self.race.as_mut() {
+            Some(view) => view,
+            None => return,
+        };
+
+        let expanded = view.race.next_step();
+        let step = view.race.step();
+        let lanes = view.race.lanes().iter().zip(view.scenes.iter_mut());
+        for ((lane, scene), point) in lanes.zip(expanded) {
+            let point = match point {
+                Some(point) => point,
+                None => continue,
+            };
+            let mut paint = |point: &Point, color| {
+                if *point != start && *point != end {
+                    scene.set_color(point, Some(color));
                 }
-            } else if let Key::P = key {
-                if args.state == ButtonState::Press {
-                    self.paused = !self.paused
+            };
+
+            let cells = lane.expanded(step);
+            if cells.len() > 1 {
+                paint(&cells[cells.len() - 2], theme.visited);
+            }
+            paint(&point, theme.current);
+            if lane.is_finished(step) {
+                match lane.path() {
+                    Some(path) => path.iter().for_each(|p| paint(p, theme.path)),
+                    None => paint(&point, theme.visited),
                 }
-            } else if let Key::S = key {
-                if args.state == ButtonState::Press {
-                    let mut maze_solver = AStarSolver::new(self.maze_generator.get_maze_ref());
+            }
+        }
+    }
 
-                    let start = &self.maze_info.start;
-                    let end = &self.maze_info.end;
+    fn race_input(&mut self, key: Key) {
+        match key {
+            Key::B => self.toggle_race(),
+            Key::R => self.start_race(),
+            Key::P => self.paused = !self.paused,
+            Key::N if self.paused => self.race_step(),
+            Key::Up | Key::Plus | Key::Equals | Key::NumPadPlus => self.change_speed(0.5),
+            Key::Down | Key::Minus | Key::NumPadMinus => self.change_speed(2.0),
+            _ => {}
+        }
+    }
 
-                    if let Some(path) = maze_solver.solve() {
-                        for node in path.iter().filter(|&p| p != start && p != end) {
-                            self.color_map.insert(*node, Some(PATH_COLOR));
-                        }
-                    }
+    fn start_comparison(&mut self) {
+        let MazeInfo {
+            width,
+            height,
+            grid,
+            ..
+        } = self.maze_info;
+        // A new seed for every comparison, unless one was given.
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let panes = GeneratorKind::ALL
+            .iter()
+            .map(|kind| GeneratorPane {
+                generator: kind.build_with_grid(grid, width, height, Some(seed)),
+                scene: Scene::with_grid(grid, width, height),
+                steps: 0,
+                stats: None,
+            })
+            .collect();
+        self.comparison = Some(ComparisonView { seed, panes });
+        self.timer = 0.0;
+    }
+
+    fn toggle_comparison(&mut self) {
+        if self.comparison.take().is_none() {
+            self.start_comparison();
+        }
+    }
+
+    fn comparison_step(&mut self) {
+        let theme = self.theme;
+        let view = match self.comparison.as_mut() {
+            Some(view) => view,
+            None => return,
+        };
+
+        for pane in view.panes.iter_mut().filter(|p| !p.generator.is_done()) {
+            let step = pane.generator.next_step();
+            if !step.cells.is_empty() || !step.walls.is_empty() {
+                pane.steps += 1;
+            }
+            for operation in step.walls {
+                if let Operation::RemoveWall(p1, p2) | Operation::AddWall(p1, p2) = operation {
+                    pane.scene.wall_changed(&p1, &p2);
                 }
             }
+            for (point, state) in step.cells {
+                pane.scene.set_color(&point, cell_color(&theme, state));
+            }
+            if pane.generator.is_done() {
+                let stats = MazeStats::compute(pane.generator.maze(), SolverKind::AStar);
+                pane.stats = Some(stats);
+            }
         }
     }
-}
 
-pub struct MazeDrawer {
-    x_offset: f64,
-    y_offset: f64,
-    cell_size: f64,
-}
+    fn comparison_input(&mut self, key: Key) {
+        match key {
+            Key::M => self.toggle_comparison(),
+            Key::R => self.start_comparison(),
+            Key::P => self.paused = !self.paused,
+            Key::N if self.paused => self.comparison_step(),
+            Key::C => while self.advance() {},
+            Key::Up | Key::Plus | Key::Equals | Key::NumPadPlus => self.change_speed(0.5),
+            Key::Down | Key::Minus | Key::NumPadMinus => self.change_speed(2.0),
+            _ => {}
+        }
+    }
 
-#[allow(dead_code)]
-impl MazeDrawer {
-    pub fn new() -> Self {
-        Self {
-            x_offset: 0.0,
-            y_offset: 0.0,
-            cell_size: 0.0,
+    // Returns `false` once there is nothing left to animate.
+    fn advance(&mut self) -> bool {
+        if self.race.is_some() {
+            self.race_step();
+            self.race
+                .as_ref()
+                .map_or(false, |view| !view.race.is_finished())
+        } else if self.comparison.is_some() {
+            self.comparison_step();
+            self.comparison
+                .as_ref()
+                .map_or(false, |view| !view.is_done())
+        } else {
+            self.step();
+            self.is_generating()
+        }
+    }
+
+    fn hud_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if let Some(view) = &self.race {
+            lines.push("race mode (b to leave, r to restart)".to_string());
+            lines.push(format!("steps: {}", view.race.step()));
+            lines.push(format!("delay: {}s", self.delay_between_steps));
+            let state = if view.race.is_finished() {
+                "done"
+            } else if self.paused {
+                "paused"
+            } else {
+                "racing"
+            };
+            lines.push(format!("state: {}", state));
+        } else if let Some(view) = &self.comparison {
+            lines.push("comparison mode (m to leave, r to restart)".to_string());
+            lines.push(format!("seed: {}", view.seed));
+            lines.push(format!("delay: {}s", self.delay_between_steps));
+            let state = if view.is_done() {
+                "done"
+            } else if self.paused {
+                "paused"
+            } else {
+                "generating"
+            };
+            lines.push(format!("state: {}", state));
+        } else if let Some(game) = &self.game {
+            lines.push("play mode (space to leave, r to retry)".to_string());
+            lines.push(format!("moves: {}", game.moves()));
+            lines.push(format!("time: {:.1}s", game.elapsed()));
+            match game.optimal() {
+                Some(optimal) => lines.push(format!("shortest path: {}", optimal)),
+                None => lines.push("shortest path: none".to_string()),
+            }
+            if game.is_finished() {
+                lines.push("finished!".to_string());
+            }
+        } else {
+            lines.push(format!("generator: {}", self.generator_kind));
+            lines.push(format!("solver: {}", self.solver_kind));
+            match self.seed {
+                Some(seed) => lines.push(format!("seed: {}", seed)),
+                None => lines.push("seed: random".to_string()),
+            }
+            lines.push(format!("steps: {}", self.steps));
+            lines.push(format!("time: {:.2}s", self.elapsed));
+            lines.push(format!("delay: {}s", self.delay_between_steps));
+            let state = if self.is_generating() {
+                if self.paused {
+                    "paused"
+                } else {
+                    "generating"
+                }
+            } else {
+                "done"
+            };
+            lines.push(format!("state: {}", state));
+            if let Some((length, nodes_expanded)) = self.solution {
+                match length {
+                    Some(length) => lines.push(format!("path length: {}", length)),
+                    None => lines.push("path length: unsolvable".to_string()),
+                }
+                lines.push(format!("nodes expanded: {}", nodes_expanded));
+            }
+        }
+
+        if let Some(message) = &self.message {
+            lines.push(message.clone());
         }
+        lines
+    }
+
+    fn change_speed(&mut self, factor: f64) {
+        self.delay_between_steps = (self.delay_between_steps * factor).clamp(MIN_DELAY, MAX_DELAY);
     }
 
-    pub fn x_offset(mut self, x_offset: f64) -> Self {
-        self.x_offset = x_offset;
-        self
+    fn cycle_generator(&mut self) {
+        self.generator_kind = next_kind(&GeneratorKind::ALL, self.generator_kind);
+        let MazeInfo {
+            width,
+            height,
+            grid,
+            ..
+        } = self.maze_info;
+        self.maze_generator = self
+            .generator_kind
+            .build_with_grid(grid, width, height, self.seed);
+        self.restart();
     }
 
-    pub fn y_offset(mut self, y_offset: f64) -> Self {
-        self.y_offset = y_offset;
-        self
+    fn cycle_solver(&mut self) {
+        self.solver_kind = next_kind(&SolverKind::ALL, self.solver_kind);
+        if self.maze_generator.is_done() {
+            self.solve();
+        }
     }
 
-    pub fn cell_size(mut self, cell_size: f64) -> Self {
-        self.set_cell_size(cell_size);
-        self
+    pub fn new(resolution: window::Size, options: &GuiOptions) -> Self {
+        let width = options.width;
+        let height = options.height;
+        let start = options.start;
+        let end = options.end;
+
+        let maze_info = MazeInfo {
+            width,
+            height,
+            grid: options.grid,
+            start,
+            end,
+        };
+
+        let mut maze_generator =
+            options
+                .algorithm
+                .build_with_grid(options.grid, width, height, options.seed);
+        maze_generator.maze_mut().set_start(start.x, start.y);
+        maze_generator.maze_mut().set_end(end.x, end.y);
+        let theme = options.theme;
+        let maze_drawer = MazeDrawer::new().wall_color(theme.wall);
+
+        let mut scene = Scene::with_grid(options.grid, width, height);
+        scene.set_color(&start, Some(theme.start));
+        scene.set_color(&end, Some(theme.end));
+
+        let mut app = Self {
+            resolution,
+            maze_drawer,
+            theme,
+            maze_info,
+            maze_generator,
+            generator_kind: options.algorithm,
+            solver_kind: SolverKind::AStar,
+            seed: options.seed,
+            timer: 0.0,
+            delay_between_steps: options.delay,
+            paused: false,
+            scene,
+            show_path: false,
+            cursor: [0.0, 0.0],
+            zoom: 1.0,
+            pressed: None,
+            drag_distance: 0.0,
+            history: History::with_limit(HISTORY_LIMIT),
+            undone_steps: 0,
+            steps: 0,
+            elapsed: 0.0,
+            solution: None,
+            show_hud: true,
+            message: None,
+            game: None,
+            race: None,
+            comparison: None,
+            session_file: options.session.clone(),
+        };
+        app.reset_view();
+        app
     }
 
-    pub fn set_cell_size(&mut self, cell_size: f64) {
-        self.cell_size = cell_size;
+    pub fn render<R: Renderer>(&mut self, renderer: &mut R) {
+        self.resolution = window::Size::from(renderer.view_size());
+        let cell_size = self.cell_size();
+        self.maze_drawer.set_cell_size(cell_size);
+        self.scene.update(self.maze_generator.maze());
+
+        renderer.clear(self.theme.back);
+        if let Some(view) = self.race.as_mut() {
+            draw_race(renderer, &self.theme, self.maze_generator.maze(), view);
+        } else if let Some(view) = self.comparison.as_mut() {
+            draw_comparison(renderer, &self.theme, view);
+        } else {
+            self.maze_drawer.draw_maze(renderer, &self.scene);
+        }
+        if let Some(game) = &self.game {
+            let MazeInfo { width, height, .. } = self.maze_info;
+            self.maze_drawer.draw_token(
+                renderer,
+                &game.position(),
+                width,
+                height,
+                self.theme.player,
+            );
+        }
+        if self.show_hud {
+            draw_text_box(renderer, &self.theme, [0.0, 0.0], &self.hud_lines());
+        }
     }
 
-    pub fn draw_maze(
-        &self,
-        c: &graphics::Context,
-        gl: &mut opengl_graphics::GlGraphics,
-        maze: &Maze,
-        color_map: &HashMap<Point, Option<Color>>,
-    ) {
-        for row in maze.cells().iter() {
-            for cell in row.iter() {
-                self.draw_cell(c, gl, &cell, *color_map.get(&cell.position).unwrap());
+    pub fn update(&mut self, args: &UpdateArgs) {
+        if let Some(game) = self.game.as_mut() {
+            game.update(args.dt);
+        } else if !self.paused {
+            if self.is_generating() && self.race.is_none() && self.comparison.is_none() {
+                self.elapsed += args.dt;
+            }
+            self.timer += args.dt;
+            if self.timer >= self.delay_between_steps {
+                let number_of_steps = (self.timer / self.delay_between_steps) as i32;
+                for _ in 0..number_of_steps {
+                    self.timer -= self.delay_between_steps;
+                    if !self.advance() {
+                        break;
+                    }
+                }
             }
         }
     }
 
-    fn to_gui_coordinates(&self, cell: &Cell) -> (f64, f64) {
-        (
-            cell.position.x as f64 * self.cell_size,
-            cell.position.y as f64 * self.cell_size,
-        )
+    pub fn input(&mut self, args: &ButtonArgs) {
+        if let Mouse(button) = args.button {
+            self.mouse_button(button, args.state);
+            return;
+        }
+        if args.state != ButtonState::Press {
+            return;
+        }
+        self.message = None;
+        if let Keyboard(Key::Home) = args.button {
+            self.reset_view();
+        } else if let Keyboard(Key::H) = args.button {
+            self.show_hud = !self.show_hud;
+        } else if let Keyboard(Key::F12) = args.button {
+            self.screenshot();
+        } else if self.race.is_some() {
+            if let Keyboard(key) = args.button {
+                self.race_input(key);
+            }
+        } else if self.comparison.is_some() {
+            if let Keyboard(key) = args.button {
+                self.comparison_input(key);
+            }
+        } else if self.game.is_some() {
+            if let Keyboard(key) = args.button {
+                self.play_input(key);
+            }
+        } else if let Keyboard(key) = args.button {
+            match key {
+                Key::R => self.restart(),
+                Key::P => self.paused = !self.paused,
+                Key::S => self.solve(),
+                Key::N if self.paused => self.step(),
+                Key::C => self.complete(),
+                Key::Up | Key::Plus | Key::Equals | Key::NumPadPlus => self.change_speed(0.5),
+                Key::Down | Key::Minus | Key::NumPadMinus => self.change_speed(2.0),
+                Key::G => self.cycle_generator(),
+                Key::V => self.cycle_solver(),
+                Key::Z | Key::Backspace => self.undo(),
+                Key::Y => self.redo(),
+                Key::Space => self.toggle_play(),
+                Key::B => self.toggle_race(),
+                Key::M => self.toggle_comparison(),
+                Key::F5 => self.save_session(),
+                Key::F9 => self.load_session(),
+                _ => {}
+            }
+        }
     }
+}
 
-    fn draw_cell(
-        &self,
-        c: &graphics::Context,
-        gl: &mut opengl_graphics::GlGraphics,
-        cell: &Cell,
-        color: Option<Color>,
-    ) {
-        use graphics::*;
+fn draw_race<R: Renderer>(renderer: &mut R, theme: &Theme, maze: &Maze, view: &mut RaceView) {
+    let layouts = pane_layouts(
+        renderer.view_size(),
+        view.scenes.len(),
+        maze.grid(),
+        maze.width,
+        maze.height,
+    );
+    let step = view.race.step();
+    let lanes = view.race.lanes().iter().zip(&mut view.scenes);
 
-        let cell_size = self.cell_size;
-        let (x, y) = self.to_gui_coordinates(cell);
+    for ((lane, scene), layout) in lanes.zip(layouts) {
+        scene.update(maze);
+        let mut lines = vec![format!(
+            "{}: {} expanded",
+            lane.kind(),
+            lane.nodes_expanded(step)
+        )];
+        if lane.is_finished(step) {
+            lines.push(match lane.path() {
+                // The path includes both the start and the end.
+                Some(path) => format!("path length: {}", path.len() - 1),
+                None => "no path found".to_string(),
+            });
+        }
+        draw_pane(renderer, theme, &layout, scene, &lines);
+    }
+}
 
-        let wall_thickness = cell_size / 15.0;
+fn draw_comparison<R: Renderer>(renderer: &mut R, theme: &Theme, view: &mut ComparisonView) {
+    let maze = view.panes[0].generator.maze();
+    let layouts = pane_layouts(
+        renderer.view_size(),
+        view.panes.len(),
+        maze.grid(),
+        maze.width,
+        maze.height,
+    );
 
-        if let Some(color) = color {
-            rectangle(color, [x, y, cell_size, cell_size], c.transform, gl);
+    for (pane, layout) in view.panes.iter_mut().zip(layouts) {
+        pane.scene.update(pane.generator.maze());
+        let mut lines = vec![format!("{}: {} steps", pane.generator.name(), pane.steps)];
+        if let Some(stats) = &pane.stats {
+            lines.push(format!("dead ends: {}", stats.dead_ends));
         }
+        draw_pane(renderer, theme, &layout, &pane.scene, &lines);
+    }
+}
 
-        for dir in cell.walls().iter() {
-            match dir {
-                Direction::North => {
-                    rectangle(
-                        WALL_COLOR,
-                        [x, y, cell_size, wall_thickness],
-                        c.transform,
-                        gl,
-                    );
-                }
-                Direction::South => {
-                    rectangle(
-                        WALL_COLOR,
-                        [x, y + cell_size - wall_thickness, cell_size, wall_thickness],
-                        c.transform,
-                        gl,
-                    );
-                }
-                Direction::East => {
-                    rectangle(
-                        WALL_COLOR,
-                        [x + cell_size - wall_thickness, y, wall_thickness, cell_size],
-                        c.transform,
-                        gl,
-                    );
-                }
-                Direction::West => {
-                    rectangle(
-                        WALL_COLOR,
-                        [x, y, wall_thickness, cell_size],
-                        c.transform,
-                        gl,
-                    );
-                }
+// Splits the view in a grid of panes and fits a maze in each of them, leaving room for
+// two lines of text below it.
+fn pane_layouts(
+    view_size: [f64; 2],
+    count: usize,
+    grid: Grid,
+    width: usize,
+    height: usize,
+) -> Vec<CellLayout> {
+    let columns = (count as f64).sqrt().ceil() as usize;
+    let rows = (count + columns - 1) / columns;
+    let pane_width = view_size[0] / columns as f64;
+    let pane_height = view_size[1] / rows as f64;
+    let margin = FONT_SIZE as f64;
+    let label_height = FONT_SIZE as f64 * 1.4 * 2.0 + FONT_SIZE as f64;
+
+    let [width, height] = grid.extent(width, height);
+    let cell_size = ((pane_width - 2.0 * margin) / width)
+        .min((pane_height - label_height - 2.0 * margin) / height)
+        .max(1.0);
+    (0..count)
+        .map(|i| {
+            let x = (i % columns) as f64 * pane_width;
+            let y = (i / columns) as f64 * pane_height;
+            CellLayout {
+                x_offset: x + (pane_width - cell_size * width) / 2.0,
+                y_offset: y + margin,
+                cell_size,
+                grid,
             }
-        }
+        })
+        .collect()
+}
+
+fn draw_pane<R: Renderer>(
+    renderer: &mut R,
+    theme: &Theme,
+    layout: &CellLayout,
+    scene: &Scene,
+    lines: &[String],
+) {
+    MazeDrawer::new()
+        .grid(layout.grid)
+        .x_offset(layout.x_offset)
+        .y_offset(layout.y_offset)
+        .cell_size(layout.cell_size)
+        .wall_color(theme.wall)
+        .draw_maze(renderer, scene);
+
+    let [_, height] = layout.extent(scene.width(), scene.height());
+    let label_y = layout.y_offset + height + FONT_SIZE as f64 / 2.0;
+    draw_text_box(renderer, theme, [layout.x_offset, label_y], lines);
+}
+
+fn draw_text_box<R: Renderer>(renderer: &mut R, theme: &Theme, [x, y]: [f64; 2], lines: &[String]) {
+    let line_height = FONT_SIZE as f64 * 1.4;
+    let margin = FONT_SIZE as f64 / 2.0;
+    // The font is monospaced.
+    let columns = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = columns as f64 * FONT_SIZE as f64 * 0.6 + 2.0 * margin;
+    let height = lines.len() as f64 * line_height + 2.0 * margin;
+    renderer.draw_rect(theme.hud_back, [x, y, width, height]);
+
+    for (i, line) in lines.iter().enumerate() {
+        let baseline = y + margin + (i + 1) as f64 * line_height - line_height / 4.0;
+        renderer.draw_text(theme.hud_text, FONT_SIZE, [x + margin, baseline], line);
     }
 }
 
+fn cell_color(theme: &Theme, state: CellState) -> Option<Color> {
+    match state {
+        CellState::Unvisited => None,
+        CellState::Visited => Some(theme.visited),
+        CellState::Current => Some(theme.current),
+    }
+}
+
+fn next_kind<T: Copy + PartialEq>(kinds: &[T], current: T) -> T {
+    let index = kinds.iter().position(|&kind| kind == current).unwrap_or(0);
+    kinds[(index + 1) % kinds.len()]
+}
+
 // fn draw_borders(width: &u32, height: &u32, c: &graphics::Context, gl: &mut GlGraphics) {
 //     use graphics::rectangle;
 
@@ -347,3 +1297,291 @@ impl MazeDrawer {
 //         gl,
 //     );
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use piston::input::Button::Keyboard;
+    use piston::input::{ButtonArgs, ButtonState, Key, UpdateArgs};
+    use piston::window;
+
+    use crate::app::{App, MAX_DELAY, MIN_DELAY};
+    use maze::cli::GuiOptions;
+    use maze::code;
+    use maze::color::to_rgb8;
+    use maze::generation::GeneratorKind;
+    use maze::grid::Grid;
+    use maze::maze::Point;
+    use maze::raster::Canvas;
+    use maze::session::Session;
+    use maze::solving::SolverKind;
+    use maze::stats::MazeStats;
+    use maze::theme::Theme;
+
+    fn options() -> GuiOptions {
+        GuiOptions {
+            window_width: 400,
+            window_height: 300,
+            width: 6,
+            height: 4,
+            grid: Grid::Square,
+            algorithm: GeneratorKind::Backtracking,
+            seed: Some(5),
+            delay: 0.005,
+            start: Point { x: 0, y: 0 },
+            end: Point { x: 5, y: 3 },
+            theme: Theme::DARK,
+            session: PathBuf::from("maze.session"),
+        }
+    }
+
+    fn app(options: &GuiOptions) -> App {
+        let resolution = (options.window_width, options.window_height);
+        App::new(window::Size::from(resolution), options)
+    }
+
+    fn press(app: &mut App, key: Key) {
+        app.input(&ButtonArgs {
+            state: ButtonState::Press,
+            button: Keyboard(key),
+            scancode: None,
+        });
+    }
+
+    fn rgba(color: [f32; 4]) -> [u8; 4] {
+        let [r, g, b] = to_rgb8(color);
+        [r, g, b, 255]
+    }
+
+    #[test]
+    fn render_headless() {
+        let mut app = app(&GuiOptions {
+            window_width: 40,
+            window_height: 20,
+            width: 4,
+            height: 2,
+            seed: Some(3),
+            end: Point { x: 3, y: 1 },
+            theme: Theme::LIGHT,
+            ..options()
+        });
+        app.show_hud = false;
+        app.complete();
+
+        let mut canvas = Canvas::new(40, 20);
+        app.render(&mut canvas);
+
+        assert_eq!(canvas.pixel(5, 5), rgba(Theme::LIGHT.start));
+        assert_eq!(canvas.pixel(15, 5), rgba(Theme::LIGHT.visited));
+
+        // Undoing every generation step brings back the blank grid.
+        while app.history.can_undo() {
+            app.undo();
+        }
+        app.render(&mut canvas);
+        assert_eq!(app.steps, 0);
+        assert_ne!(canvas.pixel(15, 5), rgba(Theme::LIGHT.visited));
+    }
+
+    #[test]
+    fn speed_keys() {
+        let mut app = app(&GuiOptions {
+            delay: 0.1,
+            ..options()
+        });
+
+        press(&mut app, Key::Up);
+        assert_eq!(app.delay_between_steps, 0.05);
+        press(&mut app, Key::Minus);
+        press(&mut app, Key::Down);
+        assert_eq!(app.delay_between_steps, 0.2);
+        for _ in 0..20 {
+            press(&mut app, Key::Down);
+        }
+        assert_eq!(app.delay_between_steps, MAX_DELAY);
+        for _ in 0..20 {
+            press(&mut app, Key::Plus);
+        }
+        assert_eq!(app.delay_between_steps, MIN_DELAY);
+    }
+
+    #[test]
+    fn step_keys() {
+        let mut app = app(&GuiOptions {
+            delay: 0.01,
+            ..options()
+        });
+        app.update(&UpdateArgs { dt: 0.035 });
+        assert_eq!(app.steps, 3);
+
+        press(&mut app, Key::P);
+        app.update(&UpdateArgs { dt: 1.0 });
+        assert_eq!(app.steps, 3);
+        press(&mut app, Key::N);
+        assert_eq!(app.steps, 4);
+
+        press(&mut app, Key::C);
+        assert!(!app.is_generating());
+        press(&mut app, Key::N);
+        assert!(!app.is_generating());
+    }
+
+    #[test]
+    fn cycle_keys() {
+        let mut app = app(&options());
+        press(&mut app, Key::C);
+        let maze = code::to_code(app.maze_generator.maze());
+        // Starting over follows the seed shown in the HUD.
+        press(&mut app, Key::R);
+        press(&mut app, Key::C);
+        assert_eq!(code::to_code(app.maze_generator.maze()), maze);
+
+        press(&mut app, Key::V);
+        assert_eq!(app.solver_kind, SolverKind::Bfs);
+        // The finished maze is solved again with the new solver.
+        assert!(app.solution.is_some());
+
+        press(&mut app, Key::G);
+        assert_eq!(app.generator_kind, GeneratorKind::Prim);
+        assert_eq!(app.maze_generator.name(), "prim");
+        assert_eq!(app.steps, 0);
+        assert!(app.is_generating());
+
+        for _ in 1..GeneratorKind::ALL.len() {
+            press(&mut app, Key::G);
+        }
+        assert_eq!(app.generator_kind, GeneratorKind::Backtracking);
+        press(&mut app, Key::C);
+        assert_eq!(code::to_code(app.maze_generator.maze()), maze);
+    }
+
+    #[test]
+    fn hud_lines() {
+        let mut app = app(&options());
+        let lines = app.hud_lines();
+        assert_eq!(
+            lines[..5],
+            [
+                "generator: backtracking",
+                "solver: astar",
+                "seed: 5",
+                "steps: 0",
+                "time: 0.00s"
+            ]
+        );
+        assert_eq!(lines.last().unwrap(), "state: generating");
+
+        press(&mut app, Key::P);
+        assert_eq!(app.hud_lines().last().unwrap(), "state: paused");
+
+        press(&mut app, Key::C);
+        press(&mut app, Key::S);
+        let length = SolverKind::AStar
+            .build(app.maze_generator.maze())
+            .solve()
+            .unwrap()
+            .len()
+            - 1;
+        let lines = app.hud_lines();
+        assert!(lines.contains(&"state: done".to_string()));
+        assert!(lines.contains(&format!("path length: {}", length)));
+        assert!(lines.last().unwrap().starts_with("nodes expanded: "));
+
+        assert!(app.show_hud);
+        press(&mut app, Key::H);
+        assert!(!app.show_hud);
+    }
+
+    #[test]
+    fn race_runs_every_solver() {
+        let mut app = app(&options());
+        app.toggle_race();
+
+        while app.advance() {}
+        let view = app.race.as_ref().unwrap();
+        assert_eq!(view.scenes.len(), SolverKind::ALL.len());
+        for (lane, scene) in view.race.lanes().iter().zip(&view.scenes) {
+            for point in lane.path().unwrap() {
+                assert_ne!(scene.color(point), None);
+            }
+        }
+
+        let mut canvas = Canvas::new(400, 300);
+        app.render(&mut canvas);
+        app.toggle_race();
+        assert!(app.race.is_none());
+    }
+
+    #[test]
+    fn comparison_uses_the_same_seed() {
+        let mut app = app(&options());
+        app.toggle_comparison();
+
+        while app.advance() {}
+        let view = app.comparison.as_ref().unwrap();
+        assert_eq!(view.seed, 5);
+        assert_eq!(view.panes.len(), GeneratorKind::ALL.len());
+        for (pane, kind) in view.panes.iter().zip(GeneratorKind::ALL.iter()) {
+            let expected = kind.build(6, 4, Some(5)).generate();
+            let stats = MazeStats::compute(&expected, SolverKind::AStar);
+            assert_eq!(pane.stats.as_ref().unwrap().dead_ends, stats.dead_ends);
+        }
+
+        let mut canvas = Canvas::new(400, 300);
+        app.render(&mut canvas);
+    }
+
+    #[test]
+    fn session_round_trip() {
+        let mut app = app(&GuiOptions {
+            grid: Grid::Hex,
+            algorithm: GeneratorKind::Wilson,
+            seed: Some(3),
+            ..options()
+        });
+        while app.advance() {}
+        app.solve();
+        let maze = app.maze_generator.maze().clone();
+        let colors = app.session().colors;
+        app.render(&mut Canvas::new(400, 300));
+        let saved = app.session().to_string();
+
+        app.restart();
+        app.open_session(Session::parse(&saved).unwrap());
+        assert_eq!(
+            code::to_code(app.maze_generator.maze()),
+            code::to_code(&maze)
+        );
+        assert_eq!(app.session().colors, colors);
+        assert!(app.maze_generator.is_done());
+
+        // Starting over generates the same maze again from its seed.
+        app.restart();
+        while app.advance() {}
+        assert_eq!(
+            code::to_code(app.maze_generator.maze()),
+            code::to_code(&maze)
+        );
+    }
+
+    #[test]
+    fn polar_session_colors() {
+        let mut app = app(&GuiOptions {
+            grid: Grid::Polar,
+            ..options()
+        });
+        press(&mut app, Key::C);
+
+        // The outer rings are wider than `width`.
+        let outer = Point {
+            x: Grid::Polar.row_width(3, 6) - 1,
+            y: 3,
+        };
+        assert!(app
+            .session()
+            .colors
+            .iter()
+            .any(|(point, _)| *point == outer));
+    }
+}
@@ -0,0 +1,937 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+use crate::code;
+use crate::color::ColorMap;
+use crate::generation::GeneratorKind;
+use crate::graph::Graph;
+use crate::grid::Grid;
+use crate::maze::{Maze, Point};
+use crate::raster::PngWriter;
+use crate::recording::GifRecorder;
+use crate::solving::{Path, SolverKind};
+use crate::stats::MazeStats;
+use crate::svg::SvgWriter;
+use crate::terminal::{Overlay, TerminalRenderer};
+use crate::theme::Theme;
+
+pub enum Command {
+    Gui(GuiOptions),
+    Generate(Options),
+    Solve(Options),
+    Render(Options),
+    Stats(Options),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+    Text,
+    Ansi,
+    Svg,
+    Png,
+    Gif,
+    Code,
+    Json,
+    Dot,
+    Edges,
+    Path,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "ansi" => Ok(Format::Ansi),
+            "svg" => Ok(Format::Svg),
+            "png" => Ok(Format::Png),
+            "gif" => Ok(Format::Gif),
+            "code" => Ok(Format::Code),
+            "json" => Ok(Format::Json),
+            "dot" => Ok(Format::Dot),
+            "edges" => Ok(Format::Edges),
+            "path" => Ok(Format::Path),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+pub struct Options {
+    pub width: usize,
+    pub height: usize,
+    pub grid: Grid,
+    pub algorithm: GeneratorKind,
+    pub solver: SolverKind,
+    pub seed: Option<u64>,
+    pub start: Option<Point>,
+    pub end: Option<Point>,
+    pub input: Option<String>,
+    pub format: Format,
+    pub output: Option<PathBuf>,
+    pub cell_size: f64,
+    pub theme: Theme,
+    pub collapse: bool,
+}
+
+pub struct GuiOptions {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub width: usize,
+    pub height: usize,
+    pub grid: Grid,
+    pub algorithm: GeneratorKind,
+    pub seed: Option<u64>,
+    pub delay: f64,
+    pub start: Point,
+    pub end: Point,
+    pub theme: Theme,
+    pub session: PathBuf,
+}
+
+const GUI_KEYS: [&str; 11] = [
+    "window",
+    "width",
+    "height",
+    "grid",
+    "algorithm",
+    "seed",
+    "delay",
+    "start",
+    "end",
+    "theme",
+    "session",
+];
+
+fn gui_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("config")
+            .short("c")
+            .long("config")
+            .value_name("FILE")
+            .help("Read options from a file of `key = value` lines, using the long option names"),
+        Arg::with_name("window")
+            .long("window")
+            .value_name("WxH")
+            .default_value("1200x600")
+            .help("Window size in pixels"),
+        Arg::with_name("width")
+            .long("width")
+            .value_name("CELLS")
+            .default_value("30")
+            .help("Number of columns"),
+        Arg::with_name("height")
+            .long("height")
+            .value_name("CELLS")
+            .default_value("20")
+            .help("Number of rows"),
+        Arg::with_name("grid")
+            .long("grid")
+            .value_name("NAME")
+            .default_value("square")
+            .help("Cell shape: square, hex, triangle or polar"),
+        Arg::with_name("algorithm")
+            .short("a")
+            .long("algorithm")
+            .value_name("NAME")
+            .default_value("backtracking")
+            .help("Generation algorithm"),
+        Arg::with_name("seed")
+            .short("s")
+            .long("seed")
+            .value_name("SEED")
+            .help("Seed for reproducible mazes"),
+        Arg::with_name("delay")
+            .short("d")
+            .long("delay")
+            .value_name("SECONDS")
+            .default_value("0.005")
+            .help("Delay between two generation steps"),
+        Arg::with_name("start")
+            .long("start")
+            .value_name("X,Y")
+            .help("Start cell, defaults to the top left corner"),
+        Arg::with_name("end")
+            .long("end")
+            .value_name("X,Y")
+            .help("End cell, defaults to the bottom right corner"),
+        Arg::with_name("theme")
+            .long("theme")
+            .value_name("NAME|FILE")
+            .default_value("dark")
+            .help("Color theme: dark, light, high-contrast, colorblind or a theme file"),
+        Arg::with_name("session")
+            .long("session")
+            .value_name("FILE")
+            .default_value("maze.session")
+            .help("File the session is saved to with F5 and loaded from with F9"),
+    ]
+}
+
+fn maze_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("width")
+            .long("width")
+            .value_name("CELLS")
+            .default_value("30")
+            .help("Number of columns"),
+        Arg::with_name("height")
+            .long("height")
+            .value_name("CELLS")
+            .default_value("20")
+            .help("Number of rows"),
+        Arg::with_name("grid")
+            .long("grid")
+            .value_name("NAME")
+            .default_value("square")
+            .help("Cell shape: square, hex, triangle or polar, ignored for input mazes"),
+        Arg::with_name("algorithm")
+            .short("a")
+            .long("algorithm")
+            .value_name("NAME")
+            .default_value("backtracking")
+            .help("Generation algorithm"),
+        Arg::with_name("solver")
+            .long("solver")
+            .value_name("NAME")
+            .default_value("astar")
+            .help("Solving algorithm"),
+        Arg::with_name("seed")
+            .short("s")
+            .long("seed")
+            .value_name("SEED")
+            .help("Seed for reproducible mazes"),
+        Arg::with_name("start")
+            .long("start")
+            .value_name("X,Y")
+            .help("Start cell, defaults to the top left corner"),
+        Arg::with_name("end")
+            .long("end")
+            .value_name("X,Y")
+            .help("End cell, defaults to the bottom right corner"),
+        Arg::with_name("input")
+            .short("i")
+            .long("input")
+            .value_name("CODE|FILE")
+            .help("Use an existing maze (a maze code, or a file with a code or JSON) instead of generating one"),
+        Arg::with_name("format")
+            .short("f")
+            .long("format")
+            .value_name("FORMAT")
+            .default_value("text")
+            .help("Output format: text, ansi, svg, png, gif, code, json, dot, edges or path"),
+        Arg::with_name("output")
+            .short("o")
+            .long("output")
+            .value_name("FILE")
+            .help("Write to a file instead of stdout"),
+        Arg::with_name("cell-size")
+            .long("cell-size")
+            .value_name("PIXELS")
+            .default_value("20")
+            .help("Cell size for svg, png and gif output"),
+        Arg::with_name("theme")
+            .long("theme")
+            .value_name("NAME|FILE")
+            .default_value("dark")
+            .help("Color theme: dark, light, high-contrast, colorblind or a theme file"),
+        Arg::with_name("collapse")
+            .long("collapse")
+            .help("Merge corridors into weighted edges for dot and edges output"),
+    ]
+}
+
+pub fn parse() -> Command {
+    let matches = app().get_matches();
+    command(&matches).unwrap_or_else(|error| {
+        clap::Error::with_description(&error, clap::ErrorKind::InvalidValue).exit()
+    })
+}
+
+fn app<'a, 'b>() -> App<'a, 'b> {
+    App::new("maze")
+        .version(clap::crate_version!())
+        .about("Maze generation and solving")
+        .setting(AppSettings::VersionlessSubcommands)
+        .args(&gui_args())
+        .subcommand(
+            SubCommand::with_name("gui")
+                .about("Open the visualisation window (default)")
+                .args(&gui_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Generate a maze")
+                .args(&maze_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("solve")
+                .about("Generate or load a maze and solve it")
+                .args(&maze_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("render")
+                .about("Render an existing maze")
+                .args(&maze_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("Print statistics about a maze")
+                .args(&maze_args()),
+        )
+}
+
+fn command(matches: &ArgMatches) -> Result<Command, String> {
+    match matches.subcommand() {
+        ("generate", Some(m)) => options(m).map(Command::Generate),
+        ("solve", Some(m)) => options(m).map(Command::Solve),
+        ("render", Some(m)) => options(m).map(Command::Render),
+        ("stats", Some(m)) => options(m).map(Command::Stats),
+        ("gui", Some(m)) => gui_options(m).map(Command::Gui),
+        _ => gui_options(matches).map(Command::Gui),
+    }
+}
+
+fn options(m: &ArgMatches) -> Result<Options, String> {
+    let options = Options {
+        width: parse_value(m, "width")?,
+        height: parse_value(m, "height")?,
+        grid: parse_value(m, "grid")?,
+        algorithm: parse_value(m, "algorithm")?,
+        solver: parse_value(m, "solver")?,
+        seed: m
+            .value_of("seed")
+            .map(str::parse)
+            .transpose()
+            .map_err(|_| format!("invalid seed '{}'", m.value_of("seed").unwrap()))?,
+        start: m.value_of("start").map(parse_point).transpose()?,
+        end: m.value_of("end").map(parse_point).transpose()?,
+        input: m.value_of("input").map(String::from),
+        format: parse_value(m, "format")?,
+        output: m.value_of("output").map(PathBuf::from),
+        cell_size: parse_value(m, "cell-size")?,
+        theme: parse_theme(m.value_of("theme").unwrap())?,
+        collapse: m.is_present("collapse"),
+    };
+
+    if options.width == 0 || options.height == 0 {
+        return Err("the maze needs at least one row and one column".to_string());
+    }
+    Ok(options)
+}
+
+// Options given on the command line take precedence over the configuration file,
+// which takes precedence over the defaults.
+fn gui_options(m: &ArgMatches) -> Result<GuiOptions, String> {
+    let config = match m.value_of("config") {
+        Some(file) => read_config(file)?,
+        None => HashMap::new(),
+    };
+    let value = |name: &str| -> Option<String> {
+        if m.occurrences_of(name) > 0 {
+            m.value_of(name).map(String::from)
+        } else {
+            config
+                .get(name)
+                .cloned()
+                .or_else(|| m.value_of(name).map(String::from))
+        }
+    };
+
+    let (window_width, window_height) = parse_size(&value("window").unwrap())?;
+    let width: usize = parse_str(&value("width").unwrap(), "width")?;
+    let height: usize = parse_str(&value("height").unwrap(), "height")?;
+    if width == 0 || height == 0 {
+        return Err("the maze needs at least one row and one column".to_string());
+    }
+
+    let options = GuiOptions {
+        window_width,
+        window_height,
+        width,
+        height,
+        grid: parse_str(&value("grid").unwrap(), "grid")?,
+        algorithm: parse_str(&value("algorithm").unwrap(), "algorithm")?,
+        seed: value("seed")
+            .map(|seed| parse_str(&seed, "seed"))
+            .transpose()?,
+        delay: parse_str(&value("delay").unwrap(), "delay")?,
+        start: match value("start") {
+            Some(start) => parse_point(&start)?,
+            None => Point { x: 0, y: 0 },
+        },
+        end: match value("end") {
+            Some(end) => parse_point(&end)?,
+            None => Point {
+                x: width - 1,
+                y: height - 1,
+            },
+        },
+        theme: parse_theme(&value("theme").unwrap())?,
+        session: PathBuf::from(value("session").unwrap()),
+    };
+
+    if options.delay <= 0.0 {
+        return Err("the delay between steps must be positive".to_string());
+    }
+    for point in [options.start, options.end] {
+        if point.y >= height || point.x >= options.grid.row_width(point.y, width) {
+            return Err(format!(
+                "({}, {}) is outside of the {}x{} maze",
+                point.x, point.y, width, height
+            ));
+        }
+    }
+    Ok(options)
+}
+
+fn read_config(file: &str) -> Result<HashMap<String, String>, String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("cannot read {}: {}", file, e))?;
+    let mut config = HashMap::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("{}:{}: expected `key = value`", file, number + 1))?;
+        let key = key.trim();
+        if !GUI_KEYS.contains(&key) {
+            return Err(format!("{}:{}: unknown option '{}'", file, number + 1, key));
+        }
+        config.insert(key.to_string(), value.trim().trim_matches('"').to_string());
+    }
+    Ok(config)
+}
+
+fn parse_value<T>(m: &ArgMatches, name: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    parse_str(m.value_of(name).unwrap(), name)
+}
+
+fn parse_str<T>(value: &str, name: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| format!("invalid value '{}' for --{}: {}", value, name, e))
+}
+
+fn parse_size(s: &str) -> Result<(u32, u32), String> {
+    match s.split_once('x') {
+        Some((width, height)) => match (width.trim().parse(), height.trim().parse()) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok((width, height)),
+            _ => Err(format!("invalid size '{}', expected WIDTHxHEIGHT", s)),
+        },
+        None => Err(format!("invalid size '{}', expected WIDTHxHEIGHT", s)),
+    }
+}
+
+// A preset name, or else a theme file.
+fn parse_theme(value: &str) -> Result<Theme, String> {
+    match value.parse() {
+        Ok(theme) => Ok(theme),
+        Err(error) if !std::path::Path::new(value).exists() => Err(error),
+        Err(_) => Theme::load(value).map_err(|e| format!("cannot load theme {}: {}", value, e)),
+    }
+}
+
+pub fn parse_point(s: &str) -> Result<Point, String> {
+    let mut coordinates = s.split(',').map(|c| c.trim().parse::<usize>());
+    match (coordinates.next(), coordinates.next(), coordinates.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Ok(Point { x, y }),
+        _ => Err(format!("invalid point '{}', expected X,Y", s)),
+    }
+}
+
+pub fn run(command: Command) -> Result<(), String> {
+    match command {
+        Command::Gui(_) => unreachable!("the window is opened by main"),
+        Command::Generate(options) => {
+            if options.input.is_some() {
+                return Err("generate does not take an input maze, use render".to_string());
+            }
+            if options.format == Format::Gif {
+                let mut generator = options.algorithm.build_with_grid(
+                    options.grid,
+                    options.width,
+                    options.height,
+                    options.seed,
+                );
+                set_endpoints(&options, generator.maze_mut())?;
+                let writer = output(&options)?;
+                return recorder(&options)
+                    .record_generation(generator.as_mut(), writer)
+                    .map_err(|e| e.to_string());
+            }
+            let maze = load_maze(&options)?;
+            write_maze(&options, &maze, None)
+        }
+        Command::Render(options) => {
+            if options.input.is_none() {
+                return Err("render needs an input maze, see --input".to_string());
+            }
+            let maze = load_maze(&options)?;
+            write_maze(&options, &maze, None)
+        }
+        Command::Solve(options) => {
+            let maze = load_maze(&options)?;
+            if options.format == Format::Gif {
+                let writer = output(&options)?;
+                return recorder(&options)
+                    .record_solving(&maze, options.solver.build(&maze).as_mut(), writer)
+                    .map_err(|e| e.to_string());
+            }
+            let path = options
+                .solver
+                .build(&maze)
+                .solve()
+                .ok_or_else(|| "the maze has no solution".to_string())?;
+            write_maze(&options, &maze, Some(&path))
+        }
+        Command::Stats(options) => {
+            let maze = load_maze(&options)?;
+            let stats = MazeStats::compute(&maze, options.solver);
+            write_bytes(&options, stats.to_string().as_bytes())
+        }
+    }
+}
+
+fn load_maze(options: &Options) -> Result<Maze, String> {
+    let mut maze = match &options.input {
+        Some(input) => {
+            let content = match fs::read_to_string(input) {
+                Ok(content) => content,
+                Err(_) => input.clone(),
+            };
+            parse_maze(content.trim())?
+        }
+        None => options
+            .algorithm
+            .build_with_grid(options.grid, options.width, options.height, options.seed)
+            .generate(),
+    };
+    set_endpoints(options, &mut maze)?;
+    Ok(maze)
+}
+
+// Moves the start and end of `maze` to those of the options, if any.
+fn set_endpoints(options: &Options, maze: &mut Maze) -> Result<(), String> {
+    for (point, is_start) in [(options.start, true), (options.end, false)] {
+        if let Some(point) = point {
+            if !maze.contains(&point) {
+                return Err(format!(
+                    "({}, {}) is outside of the {}x{} maze",
+                    point.x, point.y, maze.width, maze.height
+                ));
+            }
+            if is_start {
+                maze.set_start(point.x, point.y);
+            } else {
+                maze.set_end(point.x, point.y);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+fn parse_maze(content: &str) -> Result<Maze, String> {
+    if content.starts_with('{') {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    } else {
+        code::from_code(content).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn parse_maze(content: &str) -> Result<Maze, String> {
+    if content.starts_with('{') {
+        return Err("JSON input requires the serde feature".to_string());
+    }
+    code::from_code(content).map_err(|e| e.to_string())
+}
+
+fn write_maze(options: &Options, maze: &Maze, path: Option<&Path>) -> Result<(), String> {
+    let overlay = match path {
+        Some(path) => Overlay::Path(path),
+        None => Overlay::None,
+    };
+
+    match options.format {
+        Format::Text | Format::Ansi if !maze.grid().is_square() => Err(format!(
+            "the {:?} format is only available for square grids",
+            options.format
+        )),
+        Format::Text => write_bytes(
+            options,
+            TerminalRenderer::new()
+                .colored(false)
+                .render(maze, &overlay)
+                .as_bytes(),
+        ),
+        Format::Ansi => write_bytes(
+            options,
+            TerminalRenderer::new().render(maze, &overlay).as_bytes(),
+        ),
+        Format::Svg => write_bytes(
+            options,
+            SvgWriter::new()
+                .cell_size(options.cell_size)
+                .theme(&options.theme)
+                .render(maze, path)
+                .as_bytes(),
+        ),
+        Format::Png => {
+            let color_map = path.map(|path| path_color_map(maze, path, &options.theme));
+            PngWriter::new()
+                .cell_size(options.cell_size)
+                .theme(&options.theme)
+                .render(maze, color_map.as_ref())
+                .write_png(output(options)?)
+                .map_err(|e| e.to_string())
+        }
+        Format::Code if path.is_none() => {
+            write_bytes(options, format!("{}\n", code::to_code(maze)).as_bytes())
+        }
+        Format::Json => write_json(options, maze, path),
+        Format::Dot | Format::Edges if path.is_none() => {
+            let graph = if options.collapse {
+                Graph::collapsed(maze)
+            } else {
+                Graph::from_maze(maze)
+            };
+            let text = if options.format == Format::Dot {
+                graph.to_dot(maze)
+            } else {
+                graph.to_edge_list()
+            };
+            write_bytes(options, text.as_bytes())
+        }
+        Format::Path => match path {
+            Some(path) => {
+                let lines: Vec<String> = path.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+                write_bytes(options, format!("{}\n", lines.join("\n")).as_bytes())
+            }
+            None => Err("the path format is only available when solving".to_string()),
+        },
+        format => Err(format!(
+            "the {:?} format is not available for this command",
+            format
+        )),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn write_json(options: &Options, maze: &Maze, path: Option<&Path>) -> Result<(), String> {
+    let json = match path {
+        Some(path) => serde_json::to_string(path),
+        None => serde_json::to_string(maze),
+    }
+    .map_err(|e| e.to_string())?;
+    write_bytes(options, format!("{}\n", json).as_bytes())
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_json(_: &Options, _: &Maze, _: Option<&Path>) -> Result<(), String> {
+    Err("JSON output requires the serde feature".to_string())
+}
+
+fn path_color_map(maze: &Maze, path: &Path, theme: &Theme) -> ColorMap {
+    let mut color_map = ColorMap::new();
+    for point in path.iter() {
+        color_map.insert(*point, Some(theme.path));
+    }
+    color_map.insert(maze.start(), Some(theme.start));
+    color_map.insert(maze.end(), Some(theme.end));
+    color_map
+}
+
+fn recorder(options: &Options) -> GifRecorder {
+    GifRecorder::new()
+        .cell_size(options.cell_size)
+        .theme(&options.theme)
+}
+
+fn output(options: &Options) -> Result<Box<dyn Write>, String> {
+    match &options.output {
+        Some(file) => File::create(file)
+            .map(|file| Box::new(BufWriter::new(file)) as Box<dyn Write>)
+            .map_err(|e| format!("cannot create {}: {}", file.display(), e)),
+        None => Ok(Box::new(io::stdout())),
+    }
+}
+
+fn write_bytes(options: &Options, bytes: &[u8]) -> Result<(), String> {
+    let mut writer = output(options)?;
+    writer
+        .write_all(bytes)
+        .and_then(|_| writer.flush())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::cli::{app, command, parse_point, parse_size, parse_theme, run, Command, Format};
+    use crate::code;
+    use crate::generation::GeneratorKind;
+    use crate::graph::Graph;
+    use crate::grid::Grid;
+    use crate::maze::Point;
+    use crate::solving::SolverKind;
+
+    fn parse_args(args: &[&str]) -> Result<Command, String> {
+        let matches = app()
+            .get_matches_from_safe(std::iter::once("maze").chain(args.iter().copied()))
+            .map_err(|e| e.message)?;
+        command(&matches)
+    }
+
+    // A file of its own for every test, so that they can run in parallel.
+    fn temp_file() -> PathBuf {
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+        env::temp_dir().join(format!(
+            "maze-cli-{}-{}",
+            process::id(),
+            FILES.fetch_add(1, Ordering::SeqCst)
+        ))
+    }
+
+    fn run_args(args: &[&str]) -> Result<Vec<u8>, String> {
+        let file = temp_file();
+        let mut args = args.to_vec();
+        args.extend(["--output", file.to_str().unwrap()]);
+
+        let result = parse_args(&args).and_then(run);
+        let output = fs::read(&file);
+        let _ = fs::remove_file(&file);
+        result.map(|_| output.unwrap())
+    }
+
+    fn run_text(args: &[&str]) -> String {
+        String::from_utf8(run_args(args).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse_point("3, 4"), Ok(Point { x: 3, y: 4 }));
+        assert!(parse_point("3").is_err());
+        assert!(parse_point("3,4,5").is_err());
+        assert!(parse_point("-1,4").is_err());
+        assert_eq!(parse_size("800x600"), Ok((800, 600)));
+        assert!(parse_size("0x600").is_err());
+        assert!(parse_size("800").is_err());
+        assert!(parse_theme("light").is_ok());
+        assert!(parse_theme("no-such-theme").is_err());
+    }
+
+    #[test]
+    fn maze_options() {
+        let args = [
+            "solve", "--width", "7", "--height", "5", "--grid", "hex", "--solver", "bfs", "--seed",
+            "3", "--end", "2,2", "--format", "svg",
+        ];
+        let options = match parse_args(&args) {
+            Ok(Command::Solve(options)) => options,
+            _ => panic!("expected the solve command"),
+        };
+
+        assert_eq!((options.width, options.height), (7, 5));
+        assert_eq!(options.grid, Grid::Hex);
+        assert_eq!(options.solver, SolverKind::Bfs);
+        assert_eq!(options.seed, Some(3));
+        assert_eq!(options.start, None);
+        assert_eq!(options.end, Some(Point { x: 2, y: 2 }));
+        assert_eq!(options.format, Format::Svg);
+
+        assert!(parse_args(&["generate", "--width", "0"]).is_err());
+        assert!(parse_args(&["generate", "--format", "bmp"]).is_err());
+        assert!(parse_args(&["generate", "--seed", "x"]).is_err());
+    }
+
+    #[test]
+    fn gui_options() {
+        match parse_args(&[]) {
+            Ok(Command::Gui(options)) => {
+                assert_eq!((options.window_width, options.window_height), (1200, 600));
+                assert_eq!(options.start, Point { x: 0, y: 0 });
+                assert_eq!(options.end, Point { x: 29, y: 19 });
+            }
+            _ => panic!("expected the gui command"),
+        }
+        assert!(parse_args(&["gui", "--start", "30,0"]).is_err());
+        assert!(parse_args(&["--delay", "0"]).is_err());
+
+        // The outer rings of polar grids are wider than `width`.
+        let polar = ["--grid", "polar", "--width", "6", "--height", "4", "--end"];
+        let outer = format!("{},3", Grid::Polar.row_width(3, 6) - 1);
+        assert!(parse_args(&[&polar[..], &[outer.as_str()]].concat()).is_ok());
+        assert!(parse_args(&[&polar[..], &["6,0"]].concat()).is_err());
+    }
+
+    #[test]
+    fn config_file() {
+        let file = temp_file();
+        let config = "# Window\nwindow = 800x400\n\nwidth = 12\nalgorithm = \"prim\"\nseed=7\n";
+        fs::write(&file, config).unwrap();
+        let config = file.to_str().unwrap();
+
+        let options = match parse_args(&["--config", config, "--width", "9"]) {
+            Ok(Command::Gui(options)) => options,
+            _ => panic!("expected the gui command"),
+        };
+        assert_eq!((options.window_width, options.window_height), (800, 400));
+        // The command line takes precedence.
+        assert_eq!(options.width, 9);
+        assert_eq!(options.algorithm, GeneratorKind::Prim);
+        assert_eq!(options.seed, Some(7));
+        // Defaults fill in the rest.
+        assert_eq!(options.height, 20);
+        assert_eq!(options.end, Point { x: 8, y: 19 });
+
+        fs::write(&file, "width = 12\ncolour = red\n").unwrap();
+        let error = parse_args(&["--config", config]).err().unwrap();
+        assert!(error.ends_with(":2: unknown option 'colour'"));
+
+        fs::write(&file, "width 12\n").unwrap();
+        let error = parse_args(&["--config", config]).err().unwrap();
+        assert!(error.ends_with(":1: expected `key = value`"));
+
+        fs::write(&file, "delay = -1\n").unwrap();
+        assert!(parse_args(&["gui", "--config", config]).is_err());
+
+        fs::remove_file(&file).unwrap();
+        assert!(parse_args(&["--config", config]).is_err());
+    }
+
+    #[test]
+    fn generate_code_then_solve() {
+        let generate = [
+            "generate", "--width", "6", "--height", "4", "--seed", "5", "--format", "code",
+        ];
+        let code = run_text(&generate);
+        let maze = code::from_code(&code).unwrap();
+        assert_eq!((maze.width, maze.height), (6, 4));
+        assert_eq!(run_text(&generate), code);
+
+        let path = run_text(&["solve", "--input", code.trim(), "--format", "path"]);
+        let path: Vec<&str> = path.lines().collect();
+        let expected = SolverKind::AStar.build(&maze).solve().unwrap();
+        assert_eq!(path.len(), expected.len());
+        assert_eq!(path.first(), Some(&"0,0"));
+        assert_eq!(path.last(), Some(&"5,3"));
+
+        let stats = run_text(&["stats", "--input", code.trim()]);
+        assert!(stats.starts_with("size:            6x4\n"));
+        assert!(stats.contains(&format!("solution length: {}\n", expected.len() - 1)));
+    }
+
+    #[test]
+    fn output_formats() {
+        let output = |command: &str, format: &str| {
+            let args = [command, "--width", "4", "--height", "3", "--seed", "1"];
+            run_args(&[&args[..], &["--format", format]].concat())
+        };
+        let text = String::from_utf8(output("generate", "text").unwrap()).unwrap();
+        assert!(text.starts_with('┌') && text.contains('S') && text.contains('E'));
+        let ansi = String::from_utf8(output("solve", "ansi").unwrap()).unwrap();
+        assert!(ansi.contains("\x1b[48;2;"));
+        assert!(output("solve", "svg").unwrap().starts_with(b"<svg"));
+        assert!(output("solve", "png").unwrap().starts_with(b"\x89PNG"));
+        assert!(output("solve", "gif").unwrap().starts_with(b"GIF89a"));
+        // The start and end are also moved in recorded generations.
+        let gif = output("generate", "gif").unwrap();
+        let args = ["generate", "--width", "4", "--height", "3", "--seed", "1"];
+        let moved = run_args(&[&args[..], &["--format", "gif", "--start", "1,1"]].concat());
+        assert_ne!(moved.unwrap(), gif);
+        assert!(run_args(&[&args[..], &["--format", "gif", "--end", "4,0"]].concat()).is_err());
+        assert!(output("generate", "dot")
+            .unwrap()
+            .starts_with(b"graph maze {"));
+        // A perfect maze is a tree.
+        let edges = String::from_utf8(output("generate", "edges").unwrap()).unwrap();
+        assert_eq!(edges.lines().count(), 4 * 3 - 1);
+
+        assert!(output("generate", "path").is_err());
+        assert!(output("solve", "code").is_err());
+        assert!(output("solve", "dot").is_err());
+        assert!(run_args(&["generate", "--grid", "hex", "--format", "text"]).is_err());
+        assert!(run_args(&["render", "--format", "svg"]).is_err());
+        assert!(run_args(&["generate", "--input", "AQEBAAAAAA"]).is_err());
+        assert!(run_args(&["render", "--input", "not a code!"]).is_err());
+        assert!(run_args(&["solve", "--width", "2", "--start", "2,0"]).is_err());
+    }
+
+    #[test]
+    fn polar_two_cell_ring() {
+        let cells: usize = (0..3).map(|y| Grid::Polar.row_width(y, 2)).sum();
+        for seed in 0..20 {
+            let seed = seed.to_string();
+            let args = [
+                "--grid", "polar", "--width", "2", "--height", "3", "--seed", &seed,
+            ];
+            let path = run_text(
+                &[
+                    &["solve"][..],
+                    &args,
+                    &["--start", "1,0", "--end", "0,0", "--format", "path"],
+                ]
+                .concat(),
+            );
+            assert_eq!(path.lines().next(), Some("1,0"), "seed {}", seed);
+            assert_eq!(path.lines().last(), Some("0,0"), "seed {}", seed);
+
+            let edges = run_text(&[&["generate"][..], &args, &["--format", "edges"]].concat());
+            let mut lines: Vec<&str> = edges.lines().collect();
+            lines.sort_unstable();
+            lines.dedup();
+            assert_eq!(lines.len(), cells - 1, "seed {}", seed);
+
+            // Collapsing keeps every passage of the tree.
+            let code = run_text(&[&["generate"][..], &args, &["--format", "code"]].concat());
+            let maze = code::from_code(code.trim()).unwrap();
+            let graph = Graph::collapsed(&maze);
+            let weights: usize = graph.edges.iter().map(|edge| edge.weight).sum();
+            assert_eq!(weights, cells - 1, "seed {}", seed);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trip() {
+        let triangle = [
+            "--grid", "triangle", "--width", "5", "--height", "3", "--seed", "2",
+        ];
+        // The innermost ring has two cells.
+        let polar = [
+            "--grid", "polar", "--width", "2", "--height", "3", "--seed", "4",
+        ];
+        for args in [triangle, polar] {
+            let json = run_text(&[&["generate"][..], &args, &["--format", "json"]].concat());
+            let code = run_text(&[&["generate"][..], &args, &["--format", "code"]].concat());
+
+            assert_eq!(
+                run_text(&["render", "--input", json.trim(), "--format", "code"]),
+                code
+            );
+        }
+    }
+}
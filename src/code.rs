@@ -0,0 +1,331 @@
+use std::fmt;
+
+use crate::grid::Grid;
+use crate::maze::{Maze, Point};
+
+pub const VERSION: u8 = 1;
+// Mazes on other grids than the square one.
+pub const GRID_VERSION: u8 = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CodeError {
+    InvalidBase64,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidDimensions,
+    UnknownGrid(u8),
+    OutOfBounds(Point),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeError::InvalidBase64 => write!(f, "maze code is not valid base64"),
+            CodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported maze code version {}", version)
+            }
+            CodeError::Truncated => write!(f, "maze code is truncated"),
+            CodeError::InvalidDimensions => write!(f, "maze code has invalid dimensions"),
+            CodeError::UnknownGrid(grid) => write!(f, "maze code has an unknown grid {}", grid),
+            CodeError::OutOfBounds(point) => {
+                write!(f, "point ({}, {}) is outside of the maze", point.x, point.y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodeError {}
+
+// Layout (version 1):
+//  - version byte
+//  - width, height, start x, start y, end x, end y as LEB128 varints
+//  - one bit per interior edge, 1 meaning a wall, least significant bit first:
+//    first the east side of every cell but the last column (row-major), then the
+//    south side of every cell but the last row (row-major).
+// The outer border is always closed and is not stored.
+//
+// Version 2 has a grid byte (the index in `Grid::ALL`) after the version byte. Its edges
+// are listed cell by cell (row-major), each cell giving the sides it shares with the
+// neighbours that come after it, in the order of `Grid::directions`.
+pub fn encode(maze: &Maze) -> Vec<u8> {
+    let grid = maze.grid();
+    let mut bytes = if grid.is_square() {
+        vec![VERSION]
+    } else {
+        let index = Grid::ALL.iter().position(|g| *g == grid).unwrap();
+        vec![GRID_VERSION, index as u8]
+    };
+    for value in [
+        maze.width,
+        maze.height,
+        maze.start().x,
+        maze.start().y,
+        maze.end().x,
+        maze.end().y,
+    ] {
+        write_varint(&mut bytes, value);
+    }
+
+    let mut bits = BitWriter::default();
+    for (p1, p2) in interior_edges(grid, maze.width, maze.height) {
+        bits.push(maze.is_wall_present(&p1, &p2));
+    }
+    bytes.extend(bits.bytes);
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Maze, CodeError> {
+    let (&version, mut rest) = bytes.split_first().ok_or(CodeError::Truncated)?;
+    let grid = match version {
+        VERSION => Grid::Square,
+        GRID_VERSION => {
+            let (&index, remaining) = rest.split_first().ok_or(CodeError::Truncated)?;
+            rest = remaining;
+            *Grid::ALL
+                .get(index as usize)
+                .ok_or(CodeError::UnknownGrid(index))?
+        }
+        _ => return Err(CodeError::UnsupportedVersion(version)),
+    };
+
+    let mut header = [0; 6];
+    for value in header.iter_mut() {
+        *value = read_varint(&mut rest)?;
+    }
+    let [width, height, start_x, start_y, end_x, end_y] = header;
+
+    if width == 0 || height == 0 {
+        return Err(CodeError::InvalidDimensions);
+    }
+    // Every grid has an edge for each two cells of a row or a column, which bounds the
+    // dimensions by the length of the code before anything is computed from them.
+    let bits = rest.len().saturating_mul(8);
+    if (width.max(height) - 1) / 2 > bits {
+        return Err(CodeError::Truncated);
+    }
+    let edge_count = grid
+        .edge_count(width, height)
+        .ok_or(CodeError::InvalidDimensions)?;
+    if edge_count > bits {
+        return Err(CodeError::Truncated);
+    }
+
+    let start = Point {
+        x: start_x,
+        y: start_y,
+    };
+    let end = Point { x: end_x, y: end_y };
+    for point in [start, end] {
+        if point.y >= height || point.x >= grid.row_width(point.y, width) {
+            return Err(CodeError::OutOfBounds(point));
+        }
+    }
+
+    let mut maze = Maze::with_grid(grid, width, height);
+    maze.set_start(start.x, start.y);
+    maze.set_end(end.x, end.y);
+    let edges = interior_edges(grid, width, height);
+    for (i, (p1, p2)) in edges.iter().enumerate() {
+        if rest[i / 8] & (1 << (i % 8)) == 0 {
+            maze.remove_wall_between(p1, p2);
+        }
+    }
+    Ok(maze)
+}
+
+pub fn to_code(maze: &Maze) -> String {
+    base64::encode_config(encode(maze), base64::URL_SAFE_NO_PAD)
+}
+
+pub fn from_code(code: &str) -> Result<Maze, CodeError> {
+    let bytes = base64::decode_config(code.trim(), base64::URL_SAFE_NO_PAD)
+        .map_err(|_| CodeError::InvalidBase64)?;
+    decode(&bytes)
+}
+
+fn interior_edges(grid: Grid, width: usize, height: usize) -> Vec<(Point, Point)> {
+    let mut edges = Vec::with_capacity(grid.edge_count(width, height).unwrap_or(0));
+    if !grid.is_square() {
+        for y in 0..height {
+            for x in 0..grid.row_width(y, width) {
+                let point = Point { x, y };
+                for direction in grid.directions(&point, width, height) {
+                    match grid.neighbor(&point, direction, width, height) {
+                        Some(n) if (n.y, n.x) > (y, x) => edges.push((point, n)),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        return edges;
+    }
+    for y in 0..height {
+        for x in 0..width.saturating_sub(1) {
+            edges.push((Point { x, y }, Point { x: x + 1, y }));
+        }
+    }
+    for y in 0..height.saturating_sub(1) {
+        for x in 0..width {
+            edges.push((Point { x, y }, Point { x, y: y + 1 }));
+        }
+    }
+    edges
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<usize, CodeError> {
+    let mut value: usize = 0;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&byte, rest) = bytes.split_first().ok_or(CodeError::Truncated)?;
+        *bytes = rest;
+        value |= ((byte & 0x7f) as usize)
+            .checked_shl(shift)
+            .ok_or(CodeError::InvalidDimensions)?;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(CodeError::InvalidDimensions)
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn push(&mut self, bit: bool) {
+        if self.len % 8 == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 1 << (self.len % 8);
+        }
+        self.len += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code::{
+        decode, encode, from_code, interior_edges, to_code, write_varint, CodeError, GRID_VERSION,
+    };
+    use crate::generation::{BacktrackingGenerator, Generator, GeneratorKind};
+    use crate::grid::Grid;
+    use crate::maze::Point;
+
+    #[test]
+    fn round_trip() {
+        let mut maze = BacktrackingGenerator::new(13, 7).generate();
+        maze.set_end(6, 3);
+
+        let decoded = from_code(&to_code(&maze)).unwrap();
+
+        assert_eq!((decoded.width, decoded.height), (13, 7));
+        assert_eq!(decoded.end(), Point { x: 6, y: 3 });
+        for row in maze.cells() {
+            for cell in row {
+                assert_eq!(
+                    decoded.cell(&cell.position).sorted_walls(),
+                    cell.sorted_walls()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn other_grids_round_trip() {
+        for grid in [Grid::Hex, Grid::Triangle, Grid::Polar] {
+            let maze = GeneratorKind::Wilson
+                .build_with_grid(grid, 6, 5, Some(2))
+                .generate();
+
+            let code = to_code(&maze);
+            let decoded = from_code(&code).unwrap();
+
+            assert_eq!(decoded.grid(), grid);
+            assert_eq!(to_code(&decoded), code);
+        }
+        assert_eq!(
+            decode(&[GRID_VERSION, 9, 1, 1, 0, 0, 0, 0, 0]).err(),
+            Some(CodeError::UnknownGrid(9))
+        );
+    }
+
+    #[test]
+    fn edge_counts() {
+        for grid in Grid::ALL {
+            for width in 1..8 {
+                for height in 1..8 {
+                    assert_eq!(
+                        grid.edge_count(width, height),
+                        Some(interior_edges(grid, width, height).len()),
+                        "{} {}x{}",
+                        grid,
+                        width,
+                        height
+                    );
+                }
+            }
+        }
+        assert_eq!(Grid::Square.edge_count(usize::MAX, 2), None);
+    }
+
+    #[test]
+    fn rejects_huge_dimensions() {
+        let mut code = vec![1];
+        for value in [1 << 21, 1 << 21, 0, 0, 0, 0] {
+            write_varint(&mut code, value);
+        }
+        code.extend([0; 4]);
+        assert_eq!(decode(&code).err(), Some(CodeError::Truncated));
+
+        let mut code = vec![1];
+        for value in [1 << 40, 1 << 20, 0, 0, 0, 0] {
+            write_varint(&mut code, value);
+        }
+        code.extend([0; 4]);
+        assert_eq!(decode(&code).err(), Some(CodeError::Truncated));
+
+        // Walking the rings up to the start would take forever.
+        let mut code = vec![GRID_VERSION, 3];
+        for value in [8, 2, 0, usize::MAX / 2, 0, 0] {
+            write_varint(&mut code, value);
+        }
+        code.extend([0; 8]);
+        assert_eq!(
+            decode(&code).err(),
+            Some(CodeError::OutOfBounds(Point {
+                x: 0,
+                y: usize::MAX / 2
+            }))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        let maze = BacktrackingGenerator::new(4, 4).generate();
+        let bytes = encode(&maze);
+
+        assert_eq!(
+            decode(&bytes[..bytes.len() - 1]).err(),
+            Some(CodeError::Truncated)
+        );
+        assert_eq!(
+            decode(&[3, 4, 4, 0, 0, 3, 3]).err(),
+            Some(CodeError::UnsupportedVersion(3))
+        );
+        assert_eq!(
+            from_code("not a code!").err(),
+            Some(CodeError::InvalidBase64)
+        );
+    }
+}
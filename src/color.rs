@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use crate::maze::Point;
+
+pub type Color = [f32; 4];
+pub type ColorMap = HashMap<Point, Option<Color>>;
+
+pub const BACK_COLOR: Color = [0.204, 0.286, 0.369, 1.0];
+pub const VISITED_COLOR: Color = [0.0, 0.0, 1.0, 1.0];
+pub const CURRENT_COLOR: Color = [1.0, 1.0, 0.0, 1.0];
+pub const END_COLOR: Color = [1.0, 0.0, 0.0, 1.0];
+pub const START_COLOR: Color = [0.0, 1.0, 0.0, 1.0];
+pub const WALL_COLOR: Color = [0.0, 0.0, 0.0, 1.0];
+pub const TRAIL_COLOR: Color = [0.0, 0.6, 0.6, 1.0];
+pub const PLAYER_COLOR: Color = [1.0, 0.6, 0.0, 1.0];
+pub const HUD_BACK_COLOR: Color = [0.0, 0.0, 0.0, 0.6];
+pub const HUD_TEXT_COLOR: Color = [1.0, 1.0, 1.0, 1.0];
+pub const PATH_COLOR: Color = [0.0, 1.0, 1.0, 1.0];
+
+fn to_byte(channel: f32) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+pub fn to_rgb8(color: Color) -> [u8; 3] {
+    [to_byte(color[0]), to_byte(color[1]), to_byte(color[2])]
+}
+
+pub fn to_hex(color: Color) -> String {
+    let [r, g, b] = to_rgb8(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Parses `#rrggbb` and `#rrggbbaa`.
+pub fn from_hex(s: &str) -> Option<Color> {
+    let digits = s.strip_prefix('#')?;
+    if !(digits.len() == 6 || digits.len() == 8) || !digits.is_ascii() {
+        return None;
+    }
+    let mut color = [1.0; 4];
+    for (i, channel) in color.iter_mut().enumerate().take(digits.len() / 2) {
+        let byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).ok()?;
+        *channel = byte as f32 / 255.0;
+    }
+    Some(color)
+}
@@ -1,7 +1,13 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 
+use crate::grid::Grid;
+use crate::history::Operation;
 use crate::maze::{Maze, Point};
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -12,37 +18,144 @@ pub enum GeneratorState {
     Done,
 }
 
-#[derive(Clone, Eq, Copy, PartialEq)]
-pub enum BacktrackingCellState {
+#[derive(Clone, Eq, Copy, PartialEq, Debug)]
+pub enum CellState {
     Unvisited,
     Visited,
     Current,
 }
 
+// The cell states used to be specific to the backtracking generator, whose `next_step`,
+// `restart`, `generate` and `is_done` methods are now those of `Generator`.
+#[deprecated(note = "use `CellState`")]
+pub type BacktrackingCellState = CellState;
+
+#[derive(Default)]
+pub struct Step {
+    // Cells whose state changed during the step.
+    pub cells: Vec<(Point, CellState)>,
+    // Wall changes, already applied to the maze.
+    pub walls: Vec<Operation>,
+}
+
+pub trait Generator {
+    fn name(&self) -> &'static str;
+
+    fn maze(&self) -> &Maze;
+
+    // Editing the maze before the generation is done may confuse the generator.
+    fn maze_mut(&mut self) -> &mut Maze;
+
+    fn next_step(&mut self) -> Step;
+
+    fn restart(&mut self);
+
+    fn is_done(&self) -> bool;
+
+    fn generate(&mut self) -> Maze {
+        while !self.is_done() {
+            self.next_step();
+        }
+        self.maze().clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GeneratorKind {
+    Backtracking,
+    Prim,
+    Kruskal,
+    Wilson,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 4] = [
+        GeneratorKind::Backtracking,
+        GeneratorKind::Prim,
+        GeneratorKind::Kruskal,
+        GeneratorKind::Wilson,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorKind::Backtracking => "backtracking",
+            GeneratorKind::Prim => "prim",
+            GeneratorKind::Kruskal => "kruskal",
+            GeneratorKind::Wilson => "wilson",
+        }
+    }
+
+    pub fn build(&self, width: usize, height: usize, seed: Option<u64>) -> Box<dyn Generator> {
+        self.build_with_grid(Grid::Square, width, height, seed)
+    }
+
+    pub fn build_with_grid(
+        &self,
+        grid: Grid,
+        width: usize,
+        height: usize,
+        seed: Option<u64>,
+    ) -> Box<dyn Generator> {
+        let maze = Maze::with_grid(grid, width, height);
+        match self {
+            GeneratorKind::Backtracking => Box::new(BacktrackingGenerator::seeded(maze, seed)),
+            GeneratorKind::Prim => Box::new(PrimGenerator::seeded(maze, seed)),
+            GeneratorKind::Kruskal => Box::new(KruskalGenerator::seeded(maze, seed)),
+            GeneratorKind::Wilson => Box::new(WilsonGenerator::seeded(maze, seed)),
+        }
+    }
+}
+
+impl fmt::Display for GeneratorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for GeneratorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GeneratorKind::ALL
+            .iter()
+            .find(|kind| kind.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown generator '{}'", s))
+    }
+}
+
 pub struct BacktrackingGenerator {
     stack: Vec<Point>,
     maze: Maze,
     current: Point,
     state: GeneratorState,
-    cells_state: HashMap<Point, BacktrackingCellState>,
+    cells_state: HashMap<Point, CellState>,
+    rng: StdRng,
+    seed: Option<u64>,
     pub width: usize,
     pub height: usize,
 }
 
-#[allow(dead_code)]
 impl BacktrackingGenerator {
     pub fn new(width: usize, height: usize) -> Self {
+        Self::seeded(Maze::new(width, height), None)
+    }
+
+    pub fn with_seed(width: usize, height: usize, seed: u64) -> Self {
+        Self::seeded(Maze::new(width, height), Some(seed))
+    }
+
+    // Generates on the grid of `maze`, which should still have all of its walls.
+    fn seeded(maze: Maze, seed: Option<u64>) -> Self {
+        let (width, height) = (maze.width, maze.height);
         let stack = Vec::new();
-        let maze = Maze::new(width, height);
         let current = Point { x: 0, y: 0 };
         let state = GeneratorState::Clear;
         let mut cells_state = HashMap::with_capacity(width * height);
-        for y in 0..height {
-            for x in 0..width {
-                cells_state.insert(Point { x, y }, BacktrackingCellState::Unvisited);
-            }
+        for point in maze.points() {
+            cells_state.insert(point, CellState::Unvisited);
         }
-        cells_state.insert(current, BacktrackingCellState::Current);
+        cells_state.insert(current, CellState::Current);
 
         Self {
             stack,
@@ -52,17 +165,14 @@ impl BacktrackingGenerator {
             width,
             height,
             cells_state,
+            rng: new_rng(seed),
+            seed,
         }
     }
 
+    #[deprecated(note = "use `Generator::maze`")]
     pub fn get_maze_ref(&self) -> &Maze {
-        &self.maze
-    }
-
-    pub fn restart(&mut self) {
-        self.maze = Maze::new(self.width, self.height);
-        self.stack.clear();
-        self.state = GeneratorState::Clear;
+        Generator::maze(self)
     }
 
     fn initialize(&mut self) {
@@ -70,18 +180,56 @@ impl BacktrackingGenerator {
         self.current = start;
         self.stack.push(start);
         self.state = GeneratorState::Initialised;
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.cells_state
-                    .insert(Point { x, y }, BacktrackingCellState::Unvisited);
-            }
+        for point in self.maze.points() {
+            self.cells_state.insert(point, CellState::Unvisited);
         }
-        self.cells_state
-            .insert(start, BacktrackingCellState::Current);
+        self.cells_state.insert(start, CellState::Current);
+    }
+
+    pub fn get_cells_state(&self) -> HashMap<Point, CellState> {
+        self.cells_state.clone()
     }
 
-    pub fn next_step(&mut self) -> Vec<(Point, BacktrackingCellState)> {
-        let mut modified_cells = Vec::new();
+    fn get_random_unvisited_neighbor(&mut self, coord: Point) -> Option<Point> {
+        let neighbors: Vec<Point> = self
+            .maze
+            .neighbors(&coord)
+            .iter()
+            .filter_map(|&x| {
+                if *self.cells_state.get(&x).unwrap() == CellState::Unvisited {
+                    Some(x)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        neighbors.choose(&mut self.rng).copied()
+    }
+}
+
+impl Generator for BacktrackingGenerator {
+    fn name(&self) -> &'static str {
+        GeneratorKind::Backtracking.name()
+    }
+
+    fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    fn maze_mut(&mut self) -> &mut Maze {
+        &mut self.maze
+    }
+
+    fn restart(&mut self) {
+        self.maze = Maze::with_grid(self.maze.grid(), self.width, self.height);
+        self.rng = new_rng(self.seed);
+        self.stack.clear();
+        self.state = GeneratorState::Clear;
+    }
+
+    fn next_step(&mut self) -> Step {
+        let mut step = Step::default();
 
         if GeneratorState::Clear == self.state {
             self.initialize();
@@ -92,63 +240,496 @@ impl BacktrackingGenerator {
         } else {
             self.state = GeneratorState::InProgress;
 
-            self.cells_state
-                .insert(self.current, BacktrackingCellState::Visited);
-            modified_cells.push((self.current, BacktrackingCellState::Visited));
+            self.cells_state.insert(self.current, CellState::Visited);
+            step.cells.push((self.current, CellState::Visited));
 
             self.current = self.stack.pop().unwrap();
-            self.cells_state
-                .insert(self.current, BacktrackingCellState::Current);
-            modified_cells.push((self.current, BacktrackingCellState::Current));
+            self.cells_state.insert(self.current, CellState::Current);
+            step.cells.push((self.current, CellState::Current));
 
             if let Some(next) = self.get_random_unvisited_neighbor(self.current) {
                 self.stack.push(self.current);
 
-                let direction = self.current.relative_direction(&next);
-                let other_direction = direction.opposite();
-
-                self.maze.cell_mut(&self.current).remove_wall(&direction);
-                self.maze.cell_mut(&next).remove_wall(&other_direction);
+                let operation = Operation::RemoveWall(self.current, next);
+                operation.apply(&mut self.maze);
+                step.walls.push(operation);
 
-                self.cells_state
-                    .insert(next, BacktrackingCellState::Visited);
+                self.cells_state.insert(next, CellState::Visited);
                 self.stack.push(next);
             }
         }
-        modified_cells
+        step
     }
 
-    pub fn get_cells_state(&self) -> HashMap<Point, BacktrackingCellState> {
-        self.cells_state.clone()
+    fn is_done(&self) -> bool {
+        self.state == GeneratorState::Done
     }
+}
 
-    fn get_random_unvisited_neighbor(&self, coord: Point) -> Option<Point> {
-        let neighbors: Vec<Point> = coord
-            .neighbors(self.width, self.height)
-            .iter()
-            .filter_map(|&x| {
-                if *self.cells_state.get(&x).unwrap() == BacktrackingCellState::Unvisited {
-                    Some(x)
-                } else {
-                    None
-                }
-            })
+// A maze built beforehand, e.g. loaded from a file. There is nothing left to generate and
+// restarting keeps the maze as it is.
+pub struct PrebuiltGenerator {
+    maze: Maze,
+}
+
+impl PrebuiltGenerator {
+    pub const NAME: &'static str = "prebuilt";
+
+    pub fn new(maze: Maze) -> Self {
+        Self { maze }
+    }
+}
+
+impl Generator for PrebuiltGenerator {
+    fn name(&self) -> &'static str {
+        PrebuiltGenerator::NAME
+    }
+
+    fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    fn maze_mut(&mut self) -> &mut Maze {
+        &mut self.maze
+    }
+
+    fn next_step(&mut self) -> Step {
+        Step::default()
+    }
+
+    fn restart(&mut self) {}
+
+    fn is_done(&self) -> bool {
+        true
+    }
+}
+
+// From entropy without a seed, so that only seeded generators start over with the same maze.
+fn new_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+fn remove_wall(maze: &mut Maze, step: &mut Step, p1: Point, p2: Point) {
+    let operation = Operation::RemoveWall(p1, p2);
+    operation.apply(maze);
+    step.walls.push(operation);
+}
+
+// Grows the maze from a random cell, connecting a random cell of its frontier at each step.
+// Frontier cells are shown as current.
+pub struct PrimGenerator {
+    maze: Maze,
+    cells_state: HashMap<Point, CellState>,
+    frontier: Vec<Point>,
+    state: GeneratorState,
+    rng: StdRng,
+    seed: Option<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl PrimGenerator {
+    pub fn with_seed(width: usize, height: usize, seed: u64) -> Self {
+        Self::seeded(Maze::new(width, height), Some(seed))
+    }
+
+    fn seeded(maze: Maze, seed: Option<u64>) -> Self {
+        let (width, height) = (maze.width, maze.height);
+        Self {
+            maze,
+            cells_state: HashMap::with_capacity(width * height),
+            frontier: Vec::new(),
+            state: GeneratorState::Clear,
+            rng: new_rng(seed),
+            seed,
+            width,
+            height,
+        }
+    }
+
+    fn initialize(&mut self, step: &mut Step) {
+        for point in self.maze.points() {
+            self.cells_state.insert(point, CellState::Unvisited);
+        }
+        self.frontier.clear();
+        // Rows are never narrower than `width`.
+        let start = Point {
+            x: self.rng.gen_range(0..self.width),
+            y: self.rng.gen_range(0..self.height),
+        };
+        self.add_to_maze(start, step);
+        self.state = GeneratorState::Initialised;
+    }
+
+    fn add_to_maze(&mut self, point: Point, step: &mut Step) {
+        self.cells_state.insert(point, CellState::Visited);
+        step.cells.push((point, CellState::Visited));
+
+        for neighbor in self.maze.neighbors(&point) {
+            if self.cells_state[&neighbor] == CellState::Unvisited {
+                self.cells_state.insert(neighbor, CellState::Current);
+                step.cells.push((neighbor, CellState::Current));
+                self.frontier.push(neighbor);
+            }
+        }
+    }
+}
+
+impl Generator for PrimGenerator {
+    fn name(&self) -> &'static str {
+        GeneratorKind::Prim.name()
+    }
+
+    fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    fn maze_mut(&mut self) -> &mut Maze {
+        &mut self.maze
+    }
+
+    fn restart(&mut self) {
+        self.maze = Maze::with_grid(self.maze.grid(), self.width, self.height);
+        self.rng = new_rng(self.seed);
+        self.state = GeneratorState::Clear;
+    }
+
+    fn next_step(&mut self) -> Step {
+        let mut step = Step::default();
+
+        if self.state == GeneratorState::Clear {
+            self.initialize(&mut step);
+            return step;
+        }
+        if self.frontier.is_empty() {
+            self.state = GeneratorState::Done;
+            return step;
+        }
+        self.state = GeneratorState::InProgress;
+
+        let index = self.rng.gen_range(0..self.frontier.len());
+        let cell = self.frontier.swap_remove(index);
+        let in_maze: Vec<Point> = self
+            .maze
+            .neighbors(&cell)
+            .into_iter()
+            .filter(|neighbor| self.cells_state[neighbor] == CellState::Visited)
             .collect();
+        // Frontier cells always have a neighbor in the maze.
+        let neighbor = *in_maze.choose(&mut self.rng).unwrap();
+        remove_wall(&mut self.maze, &mut step, cell, neighbor);
+        self.add_to_maze(cell, &mut step);
+        step
+    }
+
+    fn is_done(&self) -> bool {
+        self.state == GeneratorState::Done
+    }
+}
+
+// Removes the walls in a random order, skipping the ones between cells that are already
+// connected. The two cells joined by the last step are shown as current.
+pub struct KruskalGenerator {
+    maze: Maze,
+    edges: Vec<(Point, Point)>,
+    // Union-find forest over the cells, indexed row-major with rows as wide as the widest.
+    parents: Vec<usize>,
+    columns: usize,
+    current: Vec<Point>,
+    state: GeneratorState,
+    rng: StdRng,
+    seed: Option<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl KruskalGenerator {
+    pub fn with_seed(width: usize, height: usize, seed: u64) -> Self {
+        Self::seeded(Maze::new(width, height), Some(seed))
+    }
 
-        match neighbors.choose(&mut rand::thread_rng()) {
-            Some(&n) => Some(n),
-            None => None,
+    fn seeded(maze: Maze, seed: Option<u64>) -> Self {
+        let (width, height) = (maze.width, maze.height);
+        let columns = maze.row_width(height - 1);
+        Self {
+            maze,
+            edges: Vec::new(),
+            parents: Vec::new(),
+            columns,
+            current: Vec::new(),
+            state: GeneratorState::Clear,
+            rng: new_rng(seed),
+            seed,
+            width,
+            height,
         }
     }
 
-    pub fn generate(&mut self) -> Maze {
-        while self.state != GeneratorState::Done {
-            self.next_step();
+    fn initialize(&mut self) {
+        self.edges.clear();
+        for point in self.maze.points() {
+            for neighbor in self.maze.neighbors(&point) {
+                if (neighbor.y, neighbor.x) > (point.y, point.x) {
+                    self.edges.push((point, neighbor));
+                }
+            }
+        }
+        self.edges.shuffle(&mut self.rng);
+        self.parents = (0..self.columns * self.height).collect();
+        self.current.clear();
+        self.state = GeneratorState::Initialised;
+    }
+
+    fn root(&mut self, point: &Point) -> usize {
+        let mut index = point.y * self.columns + point.x;
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+}
+
+impl Generator for KruskalGenerator {
+    fn name(&self) -> &'static str {
+        GeneratorKind::Kruskal.name()
+    }
+
+    fn maze(&self) -> &Maze {
+        &self.maze
+    }
+
+    fn maze_mut(&mut self) -> &mut Maze {
+        &mut self.maze
+    }
+
+    fn restart(&mut self) {
+        self.maze = Maze::with_grid(self.maze.grid(), self.width, self.height);
+        self.rng = new_rng(self.seed);
+        self.state = GeneratorState::Clear;
+    }
+
+    fn next_step(&mut self) -> Step {
+        let mut step = Step::default();
+
+        if self.state == GeneratorState::Clear {
+            self.initialize();
+        }
+        self.state = GeneratorState::InProgress;
+
+        for point in self.current.drain(..) {
+            step.cells.push((point, CellState::Visited));
+        }
+        while let Some((p1, p2)) = self.edges.pop() {
+            let (root1, root2) = (self.root(&p1), self.root(&p2));
+            if root1 != root2 {
+                self.parents[root1] = root2;
+                remove_wall(&mut self.maze, &mut step, p1, p2);
+                step.cells.push((p1, CellState::Current));
+                step.cells.push((p2, CellState::Current));
+                self.current = vec![p1, p2];
+                return step;
+            }
+        }
+        self.state = GeneratorState::Done;
+        step
+    }
+
+    fn is_done(&self) -> bool {
+        self.state == GeneratorState::Done
+    }
+}
+
+// Loop-erased random walks from the cells outside of the maze until they reach it, which
+// gives every possible maze the same probability. The walk is shown as current.
+pub struct WilsonGenerator {
+    maze: Maze,
+    cells_state: HashMap<Point, CellState>,
+    walk: Vec<Point>,
+    // Cells to start the next walks from, in random order.
+    remaining: Vec<Point>,
+    state: GeneratorState,
+    rng: StdRng,
+    seed: Option<u64>,
+    width: usize,
+    height: usize,
+}
+
+impl WilsonGenerator {
+    pub fn with_seed(width: usize, height: usize, seed: u64) -> Self {
+        Self::seeded(Maze::new(width, height), Some(seed))
+    }
+
+    fn seeded(maze: Maze, seed: Option<u64>) -> Self {
+        let (width, height) = (maze.width, maze.height);
+        Self {
+            maze,
+            cells_state: HashMap::with_capacity(width * height),
+            walk: Vec::new(),
+            remaining: Vec::new(),
+            state: GeneratorState::Clear,
+            rng: new_rng(seed),
+            seed,
+            width,
+            height,
+        }
+    }
+
+    fn initialize(&mut self, step: &mut Step) {
+        self.remaining = self.maze.points();
+        for point in self.remaining.iter() {
+            self.cells_state.insert(*point, CellState::Unvisited);
         }
-        self.maze.clone()
+        self.remaining.shuffle(&mut self.rng);
+        self.walk.clear();
+
+        let first = self.remaining.pop().unwrap();
+        self.cells_state.insert(first, CellState::Visited);
+        step.cells.push((first, CellState::Visited));
+        self.state = GeneratorState::Initialised;
+    }
+
+    fn set_state(&mut self, point: Point, state: CellState, step: &mut Step) {
+        self.cells_state.insert(point, state);
+        step.cells.push((point, state));
+    }
+}
+
+impl Generator for WilsonGenerator {
+    fn name(&self) -> &'static str {
+        GeneratorKind::Wilson.name()
+    }
+
+    fn maze(&self) -> &Maze {
+        &self.maze
     }
 
-    pub fn is_done(&self) -> bool {
+    fn maze_mut(&mut self) -> &mut Maze {
+        &mut self.maze
+    }
+
+    fn restart(&mut self) {
+        self.maze = Maze::with_grid(self.maze.grid(), self.width, self.height);
+        self.rng = new_rng(self.seed);
+        self.state = GeneratorState::Clear;
+    }
+
+    fn next_step(&mut self) -> Step {
+        let mut step = Step::default();
+
+        if self.state == GeneratorState::Clear {
+            self.initialize(&mut step);
+            return step;
+        }
+        self.state = GeneratorState::InProgress;
+
+        let last = match self.walk.last() {
+            Some(&last) => last,
+            None => {
+                while let Some(point) = self.remaining.pop() {
+                    if self.cells_state[&point] == CellState::Unvisited {
+                        self.walk.push(point);
+                        self.set_state(point, CellState::Current, &mut step);
+                        return step;
+                    }
+                }
+                self.state = GeneratorState::Done;
+                return step;
+            }
+        };
+
+        let next = *self.maze.neighbors(&last).choose(&mut self.rng).unwrap();
+        if self.cells_state[&next] == CellState::Visited {
+            self.walk.push(next);
+            let walk = std::mem::take(&mut self.walk);
+            for pair in walk.windows(2) {
+                remove_wall(&mut self.maze, &mut step, pair[0], pair[1]);
+            }
+            for &point in walk.iter() {
+                self.set_state(point, CellState::Visited, &mut step);
+            }
+        } else if let Some(index) = self.walk.iter().position(|&point| point == next) {
+            for point in self.walk.split_off(index + 1) {
+                self.set_state(point, CellState::Unvisited, &mut step);
+            }
+        } else {
+            self.walk.push(next);
+            self.set_state(next, CellState::Current, &mut step);
+        }
+        step
+    }
+
+    fn is_done(&self) -> bool {
         self.state == GeneratorState::Done
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::code;
+    use crate::generation::{BacktrackingGenerator, Generator, GeneratorKind};
+    use crate::grid::Grid;
+    use crate::maze::Point;
+
+    #[test]
+    fn seeded_generation_is_reproducible() {
+        let first = BacktrackingGenerator::with_seed(12, 9, 42).generate();
+        let second = BacktrackingGenerator::with_seed(12, 9, 42).generate();
+
+        for row in first.cells() {
+            for cell in row {
+                assert_eq!(
+                    second.cell(&cell.position).sorted_walls(),
+                    cell.sorted_walls()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn every_generator_builds_a_perfect_maze() {
+        let (width, height) = (9, 7);
+        for (kind, grid) in GeneratorKind::ALL
+            .iter()
+            .flat_map(|kind| Grid::ALL.iter().map(move |grid| (kind, grid)))
+        {
+            let maze = kind
+                .build_with_grid(*grid, width, height, Some(3))
+                .generate();
+
+            // Connected with one passage less than cells, so without loops.
+            let mut passages = 0;
+            let mut reached = vec![Point { x: 0, y: 0 }];
+            let mut stack = reached.clone();
+            while let Some(point) = stack.pop() {
+                for neighbor in maze.neighbors(&point) {
+                    if maze.is_wall_present(&point, &neighbor) {
+                        continue;
+                    }
+                    passages += 1;
+                    if !reached.contains(&neighbor) {
+                        reached.push(neighbor);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            let cells = maze.points().len();
+            assert_eq!(reached.len(), cells, "{} {}", kind, grid);
+            // Every passage is seen from both of its cells.
+            assert_eq!(passages / 2, cells - 1, "{} {}", kind, grid);
+        }
+    }
+
+    #[test]
+    fn restarted_generators_keep_their_seed() {
+        for kind in GeneratorKind::ALL.iter() {
+            let mut generator = kind.build_with_grid(Grid::Hex, 8, 6, Some(11));
+            let first = code::to_code(&generator.generate());
+            generator.restart();
+            assert_eq!(code::to_code(&generator.generate()), first, "{}", kind);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use graphics::{Context, Transformed};
+use opengl_graphics::{GlGraphics, GlyphCache};
+
+use maze::color::Color;
+use maze::layout::Rectangle;
+use maze::render::Renderer;
+
+pub struct GlRenderer<'a> {
+    context: Context,
+    gl: &'a mut GlGraphics,
+    glyphs: &'a mut GlyphCache<'static>,
+}
+
+impl<'a> GlRenderer<'a> {
+    pub fn new(
+        context: Context,
+        gl: &'a mut GlGraphics,
+        glyphs: &'a mut GlyphCache<'static>,
+    ) -> Self {
+        Self {
+            context,
+            gl,
+            glyphs,
+        }
+    }
+}
+
+impl<'a> Renderer for GlRenderer<'a> {
+    fn view_size(&self) -> [f64; 2] {
+        self.context
+            .viewport
+            .map(|viewport| viewport.window_size)
+            .unwrap_or([f64::MAX; 2])
+    }
+
+    fn clear(&mut self, color: Color) {
+        graphics::clear(color, self.gl);
+    }
+
+    fn draw_rect(&mut self, color: Color, rect: Rectangle) {
+        graphics::rectangle(color, rect, self.context.transform, self.gl);
+    }
+
+    fn draw_line(&mut self, color: Color, thickness: f64, line: [f64; 4]) {
+        graphics::line(
+            color,
+            thickness / 2.0,
+            line,
+            self.context.transform,
+            self.gl,
+        );
+    }
+
+    fn draw_polygon(&mut self, color: Color, corners: &[[f64; 2]]) {
+        graphics::polygon(color, corners, self.context.transform, self.gl);
+    }
+
+    fn draw_ellipse(&mut self, color: Color, rect: Rectangle) {
+        graphics::ellipse(color, rect, self.context.transform, self.gl);
+    }
+
+    fn draw_text(&mut self, color: Color, font_size: u32, [x, y]: [f64; 2], text: &str) {
+        let transform = self.context.transform.trans(x, y);
+        graphics::text(color, font_size, text, self.glyphs, transform, self.gl).ok();
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use crate::maze::{Maze, Point};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Edge {
+    pub from: Point,
+    pub to: Point,
+    pub weight: usize,
+}
+
+pub struct Graph {
+    pub nodes: Vec<Point>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    // Every cell is a node and every open wall an edge of weight 1.
+    pub fn from_maze(maze: &Maze) -> Self {
+        let mut nodes = Vec::with_capacity(maze.width * maze.height);
+        let mut edges = Vec::new();
+
+        for point in maze.points() {
+            nodes.push(point);
+            for neighbor in open_neighbors(maze, &point) {
+                if (neighbor.y, neighbor.x) > (point.y, point.x) {
+                    edges.push(Edge {
+                        from: point,
+                        to: neighbor,
+                        weight: 1,
+                    });
+                }
+            }
+        }
+        Self { nodes, edges }
+    }
+
+    // Cells with exactly two openings are merged into the edges running through them.
+    // The start and end are always kept as nodes.
+    pub fn collapsed(maze: &Maze) -> Self {
+        let is_kept = |point: &Point| {
+            *point == maze.start() || *point == maze.end() || open_neighbors(maze, point).len() != 2
+        };
+
+        let mut nodes: Vec<Point> = maze.points().into_iter().filter(is_kept).collect();
+        let mut node_set: HashSet<Point> = nodes.iter().copied().collect();
+        let mut walked: HashSet<(Point, Point)> = HashSet::new();
+        let mut visited: HashSet<Point> = node_set.clone();
+        let mut edges = Vec::new();
+
+        let mut i = 0;
+        loop {
+            while i < nodes.len() {
+                let node = nodes[i];
+                for first in open_neighbors(maze, &node) {
+                    if walked.contains(&(node, first)) {
+                        continue;
+                    }
+                    let (mut previous, mut current, mut weight) = (node, first, 1);
+                    while !node_set.contains(&current) {
+                        visited.insert(current);
+                        // A cell listing the same neighbour twice is a dead end.
+                        let next = match open_neighbors(maze, &current)
+                            .into_iter()
+                            .find(|&n| n != previous)
+                        {
+                            Some(next) => next,
+                            None => {
+                                nodes.push(current);
+                                node_set.insert(current);
+                                break;
+                            }
+                        };
+                        previous = current;
+                        current = next;
+                        weight += 1;
+                    }
+                    walked.insert((node, first));
+                    walked.insert((current, previous));
+                    edges.push(Edge {
+                        from: node,
+                        to: current,
+                        weight,
+                    });
+                }
+                i += 1;
+            }
+
+            // Loops made only of corridor cells have no node to start from.
+            match maze.points().into_iter().find(|p| !visited.contains(p)) {
+                Some(point) => {
+                    nodes.push(point);
+                    node_set.insert(point);
+                    visited.insert(point);
+                }
+                None => break,
+            }
+        }
+        Self { nodes, edges }
+    }
+
+    pub fn to_dot(&self, maze: &Maze) -> String {
+        let mut dot = String::from("graph maze {\n");
+        dot.push_str("  node [shape=circle, fixedsize=true, width=0.3, label=\"\"];\n");
+        for node in self.nodes.iter() {
+            let style = if *node == maze.start() {
+                ", style=filled, fillcolor=green"
+            } else if *node == maze.end() {
+                ", style=filled, fillcolor=red"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "  {} [xlabel=\"{},{}\", pos=\"{},{}!\"{}];",
+                node_id(node),
+                node.x,
+                node.y,
+                node.x,
+                // Graphviz's y axis points up.
+                maze.height - 1 - node.y,
+                style
+            )
+            .unwrap();
+        }
+        for edge in self.edges.iter() {
+            writeln!(
+                dot,
+                "  {} -- {} [weight={}, label=\"{}\"];",
+                node_id(&edge.from),
+                node_id(&edge.to),
+                edge.weight,
+                edge.weight
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // One `x1,y1 x2,y2 weight` line per edge.
+    pub fn to_edge_list(&self) -> String {
+        let mut list = String::new();
+        for edge in self.edges.iter() {
+            writeln!(
+                list,
+                "{},{} {},{} {}",
+                edge.from.x, edge.from.y, edge.to.x, edge.to.y, edge.weight
+            )
+            .unwrap();
+        }
+        list
+    }
+}
+
+fn node_id(point: &Point) -> String {
+    format!("c{}_{}", point.x, point.y)
+}
+
+fn open_neighbors(maze: &Maze, point: &Point) -> Vec<Point> {
+    maze.neighbors(point)
+        .into_iter()
+        .filter(|n| !maze.is_wall_present(point, n))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{Edge, Graph};
+    use crate::maze::{Maze, Point};
+
+    #[test]
+    fn collapse_corridors() {
+        // S . . E
+        //     .
+        let mut maze = Maze::new(4, 2);
+        maze.set_end(3, 0);
+        maze.remove_wall_between(&Point { x: 0, y: 0 }, &Point { x: 1, y: 0 });
+        maze.remove_wall_between(&Point { x: 1, y: 0 }, &Point { x: 2, y: 0 });
+        maze.remove_wall_between(&Point { x: 2, y: 0 }, &Point { x: 3, y: 0 });
+        maze.remove_wall_between(&Point { x: 2, y: 0 }, &Point { x: 2, y: 1 });
+
+        let graph = Graph::collapsed(&maze);
+
+        assert_eq!(
+            graph.edges,
+            vec![
+                Edge {
+                    from: Point { x: 0, y: 0 },
+                    to: Point { x: 2, y: 0 },
+                    weight: 2
+                },
+                Edge {
+                    from: Point { x: 2, y: 0 },
+                    to: Point { x: 3, y: 0 },
+                    weight: 1
+                },
+                Edge {
+                    from: Point { x: 2, y: 0 },
+                    to: Point { x: 2, y: 1 },
+                    weight: 1
+                },
+            ]
+        );
+        assert_eq!(Graph::from_maze(&maze).edges.len(), 4);
+    }
+
+    #[test]
+    fn collapse_corridor_loop() {
+        let mut maze = Maze::new(3, 3);
+        maze.set_start(0, 0);
+        maze.set_end(0, 0);
+        maze.remove_wall_between(&Point { x: 1, y: 1 }, &Point { x: 2, y: 1 });
+        maze.remove_wall_between(&Point { x: 2, y: 1 }, &Point { x: 2, y: 2 });
+        maze.remove_wall_between(&Point { x: 2, y: 2 }, &Point { x: 1, y: 2 });
+        maze.remove_wall_between(&Point { x: 1, y: 2 }, &Point { x: 1, y: 1 });
+
+        let graph = Graph::collapsed(&maze);
+
+        assert_eq!(
+            graph.edges,
+            vec![Edge {
+                from: Point { x: 1, y: 1 },
+                to: Point { x: 1, y: 1 },
+                weight: 4
+            }]
+        );
+    }
+}
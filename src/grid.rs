@@ -0,0 +1,818 @@
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::maze::{Direction, Point};
+
+const SQUARE_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+const HEX_DIRECTIONS: [Direction; 6] = [
+    Direction::NorthEast,
+    Direction::East,
+    Direction::SouthEast,
+    Direction::SouthWest,
+    Direction::West,
+    Direction::NorthWest,
+];
+
+// Triangles pointing up have a horizontal side at the bottom, triangles pointing down at
+// the top.
+const UP_TRIANGLE_DIRECTIONS: [Direction; 3] = [Direction::East, Direction::South, Direction::West];
+const DOWN_TRIANGLE_DIRECTIONS: [Direction; 3] =
+    [Direction::North, Direction::East, Direction::West];
+
+// Polar cells have an outer side, in two halves when the next ring has twice as many cells,
+// then a clockwise, an inner and a counter-clockwise side. Indexed by whether the outer side
+// is split, then by the inner side: whole, or half of the outer side of a cell that is
+// clockwise or counter-clockwise from this one.
+const POLAR_DIRECTIONS: [[&[Direction]; 3]; 2] = [
+    [
+        &[
+            Direction::Outward,
+            Direction::Clockwise,
+            Direction::Inward,
+            Direction::CounterClockwise,
+        ],
+        &[
+            Direction::Outward,
+            Direction::Clockwise,
+            Direction::InwardClockwise,
+            Direction::CounterClockwise,
+        ],
+        &[
+            Direction::Outward,
+            Direction::Clockwise,
+            Direction::InwardCounterClockwise,
+            Direction::CounterClockwise,
+        ],
+    ],
+    [
+        &[
+            Direction::OutwardCounterClockwise,
+            Direction::OutwardClockwise,
+            Direction::Clockwise,
+            Direction::Inward,
+            Direction::CounterClockwise,
+        ],
+        &[
+            Direction::OutwardCounterClockwise,
+            Direction::OutwardClockwise,
+            Direction::Clockwise,
+            Direction::InwardClockwise,
+            Direction::CounterClockwise,
+        ],
+        &[
+            Direction::OutwardCounterClockwise,
+            Direction::OutwardClockwise,
+            Direction::Clockwise,
+            Direction::InwardCounterClockwise,
+            Direction::CounterClockwise,
+        ],
+    ],
+];
+
+// Distance from the center of a hexagon to its corners, for hexagons one unit wide.
+const HEX_RADIUS: f64 = 0.577_350_269_189_625_8;
+const HEX_ROW_HEIGHT: f64 = 1.5 * HEX_RADIUS;
+// Height of a triangle with sides one unit long.
+const TRIANGLE_HEIGHT: f64 = 0.866_025_403_784_438_6;
+// Length of the segments approximating arcs.
+const ARC_STEP: f64 = 0.25;
+
+// Shape of the cells and how they connect. Geometry is in grid units: square cells are one
+// unit wide, and so are hexagons from one flat side to the other.
+//
+// Hexagons are pointy-topped and laid out in rows, odd rows being shifted half a cell to the
+// right. Cell (x, y) is the x-th hexagon of row y.
+//
+// Triangles alternate between pointing up and down along a row, cell (x, y) pointing up
+// when x + y is even. Each of them overlaps half of the next one, so a row of `width`
+// triangles is (width + 1) / 2 units wide.
+//
+// Polar grids are `height` rings one unit thick around a hole, ring 0 being the innermost
+// one, with `width` cells. Cell (x, y) is the x-th cell of ring y, clockwise from the top. A
+// ring has twice as many cells as the previous one when they would otherwise get too wide,
+// see `row_width`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Grid {
+    Square,
+    Hex,
+    Triangle,
+    Polar,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid::Square
+    }
+}
+
+// Arc of the circle of `radius` around `center`, from the `start` to the `end` angle in
+// radians. The y axis points down, so the arc is clockwise when `end` is larger.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Arc {
+    pub center: [f64; 2],
+    pub radius: f64,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Arc {
+    pub fn point(&self, angle: f64) -> [f64; 2] {
+        let [x, y] = self.center;
+        [x + self.radius * angle.cos(), y + self.radius * angle.sin()]
+    }
+
+    // Both ends included, close enough for the segments in between to look round.
+    pub fn points(&self) -> Vec<[f64; 2]> {
+        let length = (self.end - self.start).abs() * self.radius;
+        let steps = (length / ARC_STEP).ceil().max(1.0) as usize;
+        (0..=steps)
+            .map(|i| self.point(self.start + (self.end - self.start) * i as f64 / steps as f64))
+            .collect()
+    }
+}
+
+impl Grid {
+    pub const ALL: [Grid; 4] = [Grid::Square, Grid::Hex, Grid::Triangle, Grid::Polar];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Grid::Square => "square",
+            Grid::Hex => "hex",
+            Grid::Triangle => "triangle",
+            Grid::Polar => "polar",
+        }
+    }
+
+    pub fn is_square(&self) -> bool {
+        *self == Grid::Square
+    }
+
+    // `None` if it does not fit in a `usize`.
+    pub fn cell_count(&self, width: usize, height: usize) -> Option<usize> {
+        if *self != Grid::Polar {
+            return width.checked_mul(height);
+        }
+        let mut cells: usize = 0;
+        let mut rings = 0;
+        for ring in ring_widths(width).take(height) {
+            cells = cells.checked_add(ring)?;
+            rings += 1;
+        }
+        if rings == height {
+            Some(cells)
+        } else {
+            None
+        }
+    }
+
+    // Number of pairs of neighbouring cells, `None` if it does not fit in a `usize`.
+    pub fn edge_count(&self, width: usize, height: usize) -> Option<usize> {
+        if width == 0 || height == 0 {
+            return Some(0);
+        }
+        let within_rows = (width - 1).checked_mul(height)?;
+        let between_rows = match self {
+            Grid::Square => (height - 1).checked_mul(width)?,
+            Grid::Hex => (height - 1).checked_mul(2 * width - 1)?,
+            // Only the cells pointing up have a neighbour in the next row.
+            Grid::Triangle => (height / 2)
+                .checked_mul((width + 1) / 2)?
+                .checked_add(((height - 1) / 2).checked_mul(width / 2)?)?,
+            Grid::Polar => {
+                let mut edges: usize = 0;
+                let mut rings = 0;
+                for (y, cells) in ring_widths(width).take(height).enumerate() {
+                    // See `polar_neighbor` for rings of one or two cells.
+                    let around = match cells {
+                        0 | 1 => 0,
+                        2 => 1,
+                        _ => cells,
+                    };
+                    let inward = if y > 0 { cells } else { 0 };
+                    edges = edges.checked_add(around)?.checked_add(inward)?;
+                    rings += 1;
+                }
+                return if rings == height { Some(edges) } else { None };
+            }
+        };
+        within_rows.checked_add(between_rows)
+    }
+
+    // Number of cells of row `y`, the same for every row but on polar grids.
+    pub fn row_width(&self, y: usize, width: usize) -> usize {
+        match self {
+            Grid::Polar => ring_width(y, width),
+            _ => width,
+        }
+    }
+
+    // Sides of a cell in clockwise order, see `corners`.
+    pub fn directions(&self, point: &Point, width: usize, height: usize) -> &'static [Direction] {
+        match self {
+            Grid::Square => &SQUARE_DIRECTIONS,
+            Grid::Hex => &HEX_DIRECTIONS,
+            Grid::Triangle if points_up(point) => &UP_TRIANGLE_DIRECTIONS,
+            Grid::Triangle => &DOWN_TRIANGLE_DIRECTIONS,
+            Grid::Polar => {
+                let cells = ring_width(point.y, width);
+                let split = point.y + 1 < height && ring_width(point.y + 1, width) > cells;
+                let inner = if point.y > 0 && ring_width(point.y - 1, width) < cells {
+                    1 + point.x % 2
+                } else {
+                    0
+                };
+                POLAR_DIRECTIONS[split as usize][inner]
+            }
+        }
+    }
+
+    pub fn neighbor(
+        &self,
+        point: &Point,
+        direction: &Direction,
+        width: usize,
+        height: usize,
+    ) -> Option<Point> {
+        let (dx, dy) = match self {
+            Grid::Square => return point.neighbor(direction, width, height),
+            Grid::Hex => {
+                let odd = (point.y % 2) as isize;
+                match direction {
+                    Direction::East => (1, 0),
+                    Direction::West => (-1, 0),
+                    Direction::NorthEast => (odd, -1),
+                    Direction::NorthWest => (odd - 1, -1),
+                    Direction::SouthEast => (odd, 1),
+                    Direction::SouthWest => (odd - 1, 1),
+                    _ => return None,
+                }
+            }
+            Grid::Triangle => match direction {
+                Direction::East => (1, 0),
+                Direction::West => (-1, 0),
+                Direction::North if !points_up(point) => (0, -1),
+                Direction::South if points_up(point) => (0, 1),
+                _ => return None,
+            },
+            Grid::Polar => return polar_neighbor(point, direction, width, height),
+        };
+        let x = point.x as isize + dx;
+        let y = point.y as isize + dy;
+        if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+            return None;
+        }
+        Some(Point {
+            x: x as usize,
+            y: y as usize,
+        })
+    }
+
+    pub fn neighbors(&self, point: &Point, width: usize, height: usize) -> Vec<Point> {
+        match self {
+            // Kept in the historical order, seeded mazes depend on it.
+            Grid::Square => point.neighbors(width, height),
+            _ => self
+                .directions(point, width, height)
+                .iter()
+                .filter_map(|direction| self.neighbor(point, direction, width, height))
+                .collect(),
+        }
+    }
+
+    // Number of moves between two cells when no wall is in the way. Only a lower bound on
+    // polar grids: the number of rings in between.
+    pub fn distance(&self, p1: &Point, p2: &Point) -> usize {
+        match self {
+            Grid::Square => p1.distance(p2),
+            Grid::Hex => {
+                // Axial coordinates, where the third cube coordinate is -q - r.
+                let axial = |p: &Point| {
+                    let (x, y) = (p.x as isize, p.y as isize);
+                    (x - (y - (y & 1)) / 2, y)
+                };
+                let ((q1, r1), (q2, r2)) = (axial(p1), axial(p2));
+                let (dq, dr) = (q1 - q2, r1 - r2);
+                ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+            }
+            Grid::Triangle => {
+                // Every move crosses one of the lines splitting the plane into triangles:
+                // the horizontal ones, and the two diagonal ones, the cells between two
+                // lines of a family forming a band.
+                let bands = |p: &Point| {
+                    let (x, y) = (p.x as isize, p.y as isize);
+                    (y, (x + y).div_euclid(2), (x - y + 1).div_euclid(2))
+                };
+                let ((a1, b1, c1), (a2, b2, c2)) = (bands(p1), bands(p2));
+                ((a1 - a2).abs() + (b1 - b2).abs() + (c1 - c2).abs()) as usize
+            }
+            Grid::Polar => p1.y.abs_diff(p2.y),
+        }
+    }
+
+    // Size of a `width` x `height` maze.
+    pub fn extent(&self, width: usize, height: usize) -> [f64; 2] {
+        match self {
+            Grid::Square => [width as f64, height as f64],
+            Grid::Hex => {
+                let shift = if height > 1 { 0.5 } else { 0.0 };
+                [
+                    width as f64 + shift,
+                    2.0 * HEX_RADIUS + (height - 1) as f64 * HEX_ROW_HEIGHT,
+                ]
+            }
+            Grid::Triangle => [(width + 1) as f64 / 2.0, height as f64 * TRIANGLE_HEIGHT],
+            Grid::Polar => {
+                let diameter = 2.0 * (polar_hole(width) + height as f64);
+                [diameter, diameter]
+            }
+        }
+    }
+
+    pub fn center(&self, point: &Point, width: usize, height: usize) -> [f64; 2] {
+        let (x, y) = (point.x as f64, point.y as f64);
+        match self {
+            Grid::Square => [x + 0.5, y + 0.5],
+            Grid::Hex => [
+                x + 0.5 + (point.y % 2) as f64 * 0.5,
+                HEX_RADIUS + y * HEX_ROW_HEIGHT,
+            ],
+            // Centroids, a third of the height away from the horizontal side.
+            Grid::Triangle if points_up(point) => {
+                [x / 2.0 + 0.5, (y + 2.0 / 3.0) * TRIANGLE_HEIGHT]
+            }
+            Grid::Triangle => [x / 2.0 + 0.5, (y + 1.0 / 3.0) * TRIANGLE_HEIGHT],
+            Grid::Polar => {
+                let ring = polar_ring(point, width, height, 0.5);
+                ring.point(ring.start + (ring.end - ring.start) * 0.5)
+            }
+        }
+    }
+
+    // Corners in clockwise order, side `i` of `directions` going from corner `i` to `i + 1`.
+    pub fn corners(&self, point: &Point, width: usize, height: usize) -> Vec<[f64; 2]> {
+        let [x, y] = self.center(point, width, height);
+        match self {
+            Grid::Square => vec![
+                [x - 0.5, y - 0.5],
+                [x + 0.5, y - 0.5],
+                [x + 0.5, y + 0.5],
+                [x - 0.5, y + 0.5],
+            ],
+            Grid::Hex => {
+                let r = HEX_RADIUS;
+                vec![
+                    [x, y - r],
+                    [x + 0.5, y - r / 2.0],
+                    [x + 0.5, y + r / 2.0],
+                    [x, y + r],
+                    [x - 0.5, y + r / 2.0],
+                    [x - 0.5, y - r / 2.0],
+                ]
+            }
+            Grid::Triangle => {
+                let top = point.y as f64 * TRIANGLE_HEIGHT;
+                let bottom = top + TRIANGLE_HEIGHT;
+                if points_up(point) {
+                    vec![[x, top], [x + 0.5, bottom], [x - 0.5, bottom]]
+                } else {
+                    vec![[x - 0.5, top], [x + 0.5, top], [x, bottom]]
+                }
+            }
+            Grid::Polar => {
+                let outer = polar_ring(point, width, height, 1.0);
+                let inner = polar_ring(point, width, height, 0.0);
+                let mut corners = vec![outer.point(outer.start)];
+                if self.directions(point, width, height).len() == 5 {
+                    corners.push(outer.point((outer.start + outer.end) / 2.0));
+                }
+                corners.extend([
+                    outer.point(outer.end),
+                    inner.point(inner.end),
+                    inner.point(inner.start),
+                ]);
+                corners
+            }
+        }
+    }
+
+    // Segment between the two corners of a side, `None` if the cells have no such side.
+    pub fn side(
+        &self,
+        point: &Point,
+        direction: &Direction,
+        width: usize,
+        height: usize,
+    ) -> Option<[f64; 4]> {
+        let directions = self.directions(point, width, height);
+        let i = directions.iter().position(|d| d == direction)?;
+        let corners = self.corners(point, width, height);
+        let ([x1, y1], [x2, y2]) = (corners[i], corners[(i + 1) % corners.len()]);
+        Some([x1, y1, x2, y2])
+    }
+
+    // The inner and outer sides of polar cells are arcs, going the same way as `side`.
+    pub fn arc(
+        &self,
+        point: &Point,
+        direction: &Direction,
+        width: usize,
+        height: usize,
+    ) -> Option<Arc> {
+        if *self != Grid::Polar || !self.directions(point, width, height).contains(direction) {
+            return None;
+        }
+        let (outer, inner) = (
+            polar_ring(point, width, height, 1.0),
+            polar_ring(point, width, height, 0.0),
+        );
+        let middle = (outer.start + outer.end) / 2.0;
+        match direction {
+            Direction::Outward => Some(outer),
+            Direction::OutwardCounterClockwise => Some(Arc {
+                end: middle,
+                ..outer
+            }),
+            Direction::OutwardClockwise => Some(Arc {
+                start: middle,
+                ..outer
+            }),
+            Direction::Inward | Direction::InwardClockwise | Direction::InwardCounterClockwise => {
+                Some(Arc {
+                    start: inner.end,
+                    end: inner.start,
+                    ..inner
+                })
+            }
+            _ => None,
+        }
+    }
+
+    // Segments to draw a side with, arcs being approximated by short ones.
+    pub fn side_segments(
+        &self,
+        point: &Point,
+        direction: &Direction,
+        width: usize,
+        height: usize,
+    ) -> Vec<[f64; 4]> {
+        match self.arc(point, direction, width, height) {
+            Some(arc) => arc
+                .points()
+                .windows(2)
+                .map(|w| [w[0][0], w[0][1], w[1][0], w[1][1]])
+                .collect(),
+            None => self
+                .side(point, direction, width, height)
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    // Convex polygons covering a cell with clockwise corners, as polar cells are curved.
+    pub fn tiles(&self, point: &Point, width: usize, height: usize) -> Vec<Vec<[f64; 2]>> {
+        if *self != Grid::Polar {
+            return vec![self.corners(point, width, height)];
+        }
+        let (outer, inner) = (
+            polar_ring(point, width, height, 1.0).points(),
+            polar_ring(point, width, height, 0.0),
+        );
+        let steps = outer.len() - 1;
+        let angle = |i: usize| inner.start + (inner.end - inner.start) * i as f64 / steps as f64;
+        (0..steps)
+            .map(|i| {
+                vec![
+                    outer[i],
+                    outer[i + 1],
+                    inner.point(angle(i + 1)),
+                    inner.point(angle(i)),
+                ]
+            })
+            .collect()
+    }
+
+    // Cell of a `width` x `height` maze containing the position, if any.
+    pub fn locate(&self, x: f64, y: f64, width: usize, height: usize) -> Option<Point> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (column, row) = match self {
+            Grid::Square => (x.floor() as usize, y.floor() as usize),
+            Grid::Hex => {
+                // The hexagon is in this row and column or in the previous ones.
+                let (row, column) = ((y / HEX_ROW_HEIGHT) as usize, x as usize);
+                let rows = row.saturating_sub(1)..(row + 1).min(height);
+                let columns = column.saturating_sub(1)..(column + 1).min(width);
+                return rows
+                    .flat_map(|r| columns.clone().map(move |c| Point { x: c, y: r }))
+                    .find(|point| contains(&self.corners(point, width, height), [x, y]));
+            }
+            Grid::Triangle => {
+                // The triangle is one of the two overlapping this half unit of the row.
+                let (row, column) = ((y / TRIANGLE_HEIGHT) as usize, (2.0 * x) as usize);
+                if row >= height {
+                    return None;
+                }
+                return (column.saturating_sub(1)..(column + 1).min(width))
+                    .map(|c| Point { x: c, y: row })
+                    .find(|point| contains(&self.corners(point, width, height), [x, y]));
+            }
+            Grid::Polar => {
+                let hole = polar_hole(width);
+                let (dx, dy) = (x - hole - height as f64, y - hole - height as f64);
+                let ring = dx.hypot(dy) - hole;
+                if ring < 0.0 || ring >= height as f64 {
+                    return None;
+                }
+                let row = ring as usize;
+                let cells = ring_width(row, width);
+                let turns = (dy.atan2(dx) / (2.0 * PI) + 0.25).rem_euclid(1.0);
+                ((turns * cells as f64) as usize, row)
+            }
+        };
+        if column >= self.row_width(row, width) || row >= height {
+            return None;
+        }
+        Some(Point { x: column, y: row })
+    }
+}
+
+fn points_up(point: &Point) -> bool {
+    (point.x + point.y) % 2 == 0
+}
+
+// Radius of the hole in the middle of polar grids, for the cells of the innermost ring to
+// be one unit wide on their inner side.
+fn polar_hole(width: usize) -> f64 {
+    width as f64 / (2.0 * PI)
+}
+
+// The cells of a ring are doubled when they would otherwise be more than one and a half
+// units wide in its middle.
+fn ring_widths(width: usize) -> impl Iterator<Item = usize> {
+    let hole = polar_hole(width);
+    let mut ring = 0;
+    std::iter::successors(Some(width), move |&cells| {
+        ring += 1;
+        let circumference = 2.0 * PI * (hole + ring as f64 + 0.5);
+        if circumference > 1.5 * cells as f64 {
+            cells.checked_mul(2)
+        } else {
+            Some(cells)
+        }
+    })
+}
+
+fn ring_width(y: usize, width: usize) -> usize {
+    ring_widths(width)
+        .nth(y)
+        .expect("too many cells in the ring")
+}
+
+// Arc across a polar cell, from its counter-clockwise to its clockwise side, `depth` being
+// 0 on its inner side and 1 on its outer side.
+fn polar_ring(point: &Point, width: usize, height: usize, depth: f64) -> Arc {
+    let hole = polar_hole(width);
+    let middle = hole + height as f64;
+    let cells = ring_width(point.y, width) as f64;
+    // Angles are measured clockwise from the right, the first cell starts at the top.
+    let angle = |x: f64| (x / cells - 0.25) * 2.0 * PI;
+    Arc {
+        center: [middle, middle],
+        radius: hole + point.y as f64 + depth,
+        start: angle(point.x as f64),
+        end: angle(point.x as f64 + 1.0),
+    }
+}
+
+fn polar_neighbor(
+    point: &Point,
+    direction: &Direction,
+    width: usize,
+    height: usize,
+) -> Option<Point> {
+    if !Grid::Polar
+        .directions(point, width, height)
+        .contains(direction)
+    {
+        return None;
+    }
+    let (x, y) = (point.x, point.y);
+    let cells = ring_width(y, width);
+    // In rings of one or two cells, the sides at the top are walls: a cell would otherwise be
+    // its own neighbour, or the two cells neighbours on both sides.
+    let (x, y) = match direction {
+        Direction::Clockwise if x + 1 < cells || cells > 2 => ((x + 1) % cells, y),
+        Direction::CounterClockwise if x > 0 || cells > 2 => ((x + cells - 1) % cells, y),
+        Direction::Inward if y > 0 => (x, y - 1),
+        Direction::InwardClockwise | Direction::InwardCounterClockwise => (x / 2, y - 1),
+        Direction::Outward if y + 1 < height => (x, y + 1),
+        Direction::OutwardCounterClockwise => (2 * x, y + 1),
+        Direction::OutwardClockwise => (2 * x + 1, y + 1),
+        _ => return None,
+    };
+    Some(Point { x, y })
+}
+
+// Whether a convex polygon with clockwise corners contains the position.
+fn contains(corners: &[[f64; 2]], [x, y]: [f64; 2]) -> bool {
+    (0..corners.len()).all(|i| {
+        let ([x1, y1], [x2, y2]) = (corners[i], corners[(i + 1) % corners.len()]);
+        (x2 - x1) * (y - y1) - (y2 - y1) * (x - x1) >= 0.0
+    })
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::ALL
+            .iter()
+            .find(|grid| grid.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown grid '{}'", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+
+    use crate::grid::Grid;
+    use crate::maze::{Direction, Point};
+
+    #[test]
+    fn neighbors_share_a_side() {
+        for grid in Grid::ALL.iter() {
+            for y in 0..4 {
+                for x in 0..grid.row_width(y, 4) {
+                    let point = Point { x, y };
+                    for direction in grid.directions(&point, 4, 4) {
+                        let neighbor = match grid.neighbor(&point, direction, 4, 4) {
+                            Some(neighbor) => neighbor,
+                            None => continue,
+                        };
+                        // Only a lower bound on polar grids.
+                        assert!(grid.distance(&point, &neighbor) <= 1);
+                        let [x1, y1, x2, y2] = grid.side(&point, direction, 4, 4).unwrap();
+                        let [x3, y3, x4, y4] =
+                            grid.side(&neighbor, &direction.opposite(), 4, 4).unwrap();
+                        assert!((x1 - x4).abs() < 1e-9 && (y1 - y4).abs() < 1e-9);
+                        assert!((x2 - x3).abs() < 1e-9 && (y2 - y3).abs() < 1e-9);
+                    }
+                }
+            }
+        }
+
+        let grid = Grid::Hex;
+        assert_eq!(grid.neighbors(&Point { x: 0, y: 0 }, 4, 4).len(), 2);
+        assert_eq!(grid.neighbors(&Point { x: 1, y: 1 }, 4, 4).len(), 6);
+        assert_eq!(
+            grid.distance(&Point { x: 0, y: 0 }, &Point { x: 3, y: 3 }),
+            5
+        );
+        assert_eq!(
+            grid.neighbor(&Point { x: 0, y: 0 }, &Direction::North, 4, 4),
+            None
+        );
+    }
+
+    #[test]
+    fn polar_neighbors_are_mutual() {
+        // Rings of one and two cells included.
+        for width in 1..5 {
+            for y in 0..3 {
+                for x in 0..Grid::Polar.row_width(y, width) {
+                    let point = Point { x, y };
+                    let neighbors = Grid::Polar.neighbors(&point, width, 3);
+                    for (i, neighbor) in neighbors.iter().enumerate() {
+                        assert_ne!(*neighbor, point);
+                        assert!(!neighbors[..i].contains(neighbor));
+                    }
+                    for direction in Grid::Polar.directions(&point, width, 3) {
+                        if let Some(neighbor) = Grid::Polar.neighbor(&point, direction, width, 3) {
+                            assert_eq!(
+                                Grid::Polar.neighbor(&neighbor, &direction.opposite(), width, 3),
+                                Some(point)
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn triangle_distances() {
+        let grid = Grid::Triangle;
+        assert_eq!(grid.neighbors(&Point { x: 1, y: 1 }, 4, 4).len(), 3);
+        assert_eq!(
+            grid.neighbor(&Point { x: 1, y: 0 }, &Direction::South, 4, 4),
+            None
+        );
+
+        // Breadth-first search from every cell of an open grid.
+        let (width, height) = (7, 5);
+        for y in 0..height {
+            for x in 0..width {
+                let start = Point { x, y };
+                let mut distances = HashMap::new();
+                distances.insert(start, 0);
+                let mut queue = VecDeque::from(vec![start]);
+                while let Some(point) = queue.pop_front() {
+                    let distance = distances[&point];
+                    for neighbor in grid.neighbors(&point, width, height) {
+                        distances.entry(neighbor).or_insert_with(|| {
+                            queue.push_back(neighbor);
+                            distance + 1
+                        });
+                    }
+                }
+                for (point, distance) in distances {
+                    assert_eq!(grid.distance(&start, &point), distance);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn polar_rings() {
+        let grid = Grid::Polar;
+        let widths: Vec<usize> = (0..4).map(|y| grid.row_width(y, 8)).collect();
+        assert_eq!(widths, vec![8, 16, 16, 32]);
+
+        // The cells of ring 0 have two outward neighbours and the first ring wraps around.
+        let neighbors = |x, y| grid.neighbors(&Point { x, y }, 8, 4);
+        assert_eq!(
+            neighbors(0, 0),
+            vec![
+                Point { x: 0, y: 1 },
+                Point { x: 1, y: 1 },
+                Point { x: 1, y: 0 },
+                Point { x: 7, y: 0 }
+            ]
+        );
+        assert_eq!(
+            neighbors(15, 1),
+            vec![
+                Point { x: 15, y: 2 },
+                Point { x: 0, y: 1 },
+                Point { x: 7, y: 0 },
+                Point { x: 14, y: 1 }
+            ]
+        );
+        // No outward neighbour on the outer ring.
+        assert_eq!(neighbors(0, 3).len(), 3);
+
+        // Arcs end on the corners of the cell.
+        let point = Point { x: 3, y: 1 };
+        for direction in grid.directions(&point, 8, 4) {
+            let [x1, y1, x2, y2] = grid.side(&point, direction, 8, 4).unwrap();
+            if let Some(arc) = grid.arc(&point, direction, 8, 4) {
+                let ([x3, y3], [x4, y4]) = (arc.point(arc.start), arc.point(arc.end));
+                assert!((x1 - x3).abs() < 1e-9 && (y1 - y3).abs() < 1e-9);
+                assert!((x2 - x4).abs() < 1e-9 && (y2 - y4).abs() < 1e-9);
+            }
+        }
+        assert_eq!(grid.arc(&point, &Direction::Clockwise, 8, 4), None);
+    }
+
+    #[test]
+    fn locate_cells() {
+        for grid in Grid::ALL.iter() {
+            for y in 0..3 {
+                for x in 0..grid.row_width(y, 5) {
+                    let point = Point { x, y };
+                    let [cx, cy] = grid.center(&point, 5, 3);
+                    assert_eq!(grid.locate(cx, cy, 5, 3), Some(point), "{}", grid);
+                }
+            }
+            let [width, height] = grid.extent(5, 3);
+            assert_eq!(grid.locate(width + 0.1, 0.5, 5, 3), None);
+            assert_eq!(grid.locate(0.5, height + 0.1, 5, 3), None);
+        }
+        // The top left corner of the grid is outside of the first hexagon.
+        assert_eq!(Grid::Hex.locate(0.05, 0.05, 5, 3), None);
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+
+use crate::maze::{Maze, Point};
+
+pub trait Reversible {
+    fn reversed(&self) -> Self;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    RemoveWall(Point, Point),
+    AddWall(Point, Point),
+    MoveStart { from: Point, to: Point },
+    MoveEnd { from: Point, to: Point },
+}
+
+impl Operation {
+    pub fn apply(&self, maze: &mut Maze) {
+        match *self {
+            Operation::RemoveWall(p1, p2) => maze.remove_wall_between(&p1, &p2),
+            Operation::AddWall(p1, p2) => maze.add_wall_between(&p1, &p2),
+            Operation::MoveStart { to, .. } => maze.set_start(to.x, to.y),
+            Operation::MoveEnd { to, .. } => maze.set_end(to.x, to.y),
+        }
+    }
+}
+
+impl Reversible for Operation {
+    fn reversed(&self) -> Self {
+        match *self {
+            Operation::RemoveWall(p1, p2) => Operation::AddWall(p1, p2),
+            Operation::AddWall(p1, p2) => Operation::RemoveWall(p1, p2),
+            Operation::MoveStart { from, to } => Operation::MoveStart { from: to, to: from },
+            Operation::MoveEnd { from, to } => Operation::MoveEnd { from: to, to: from },
+        }
+    }
+}
+
+// A group of changes is undone in the opposite order.
+impl<T: Reversible> Reversible for Vec<T> {
+    fn reversed(&self) -> Self {
+        self.iter().rev().map(Reversible::reversed).collect()
+    }
+}
+
+pub struct History<T> {
+    done: VecDeque<T>,
+    undone: Vec<T>,
+    limit: usize,
+}
+
+impl<T: Reversible> Default for History<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Reversible> History<T> {
+    pub fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    // The oldest entries are forgotten once more than `limit` are recorded, nothing is
+    // recorded with a limit of 0.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            done: VecDeque::new(),
+            undone: Vec::new(),
+            limit,
+        }
+    }
+
+    // Recording a new entry drops everything that was undone.
+    pub fn record(&mut self, entry: T) {
+        self.undone.clear();
+        if self.limit == 0 {
+            return;
+        }
+        while self.done.len() >= self.limit {
+            self.done.pop_front();
+        }
+        self.done.push_back(entry);
+    }
+
+    // Returns the changes to apply to revert the last entry.
+    pub fn undo(&mut self) -> Option<T> {
+        let entry = self.done.pop_back()?;
+        let reversed = entry.reversed();
+        self.undone.push(entry);
+        Some(reversed)
+    }
+
+    // Returns the changes to apply to restore the last undone entry.
+    pub fn redo(&mut self) -> Option<&T> {
+        let entry = self.undone.pop()?;
+        self.done.push_back(entry);
+        self.done.back()
+    }
+
+    pub fn next_redo(&self) -> Option<&T> {
+        self.undone.last()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::{History, Operation, Reversible};
+    use crate::maze::{Maze, Point};
+
+    #[test]
+    fn undo_and_redo_walls() {
+        let (a, b, c) = (
+            Point { x: 0, y: 0 },
+            Point { x: 1, y: 0 },
+            Point { x: 1, y: 1 },
+        );
+        let mut maze = Maze::new(2, 2);
+        let mut history = History::new();
+
+        for operations in [
+            vec![Operation::RemoveWall(a, b)],
+            vec![
+                Operation::RemoveWall(b, c),
+                Operation::MoveEnd { from: c, to: b },
+            ],
+        ] {
+            operations.iter().for_each(|op| op.apply(&mut maze));
+            history.record(operations);
+        }
+
+        history
+            .undo()
+            .unwrap()
+            .iter()
+            .for_each(|op| op.apply(&mut maze));
+        assert!(maze.is_wall_present(&b, &c));
+        assert!(!maze.is_wall_present(&a, &b));
+        assert_eq!(maze.end(), c);
+
+        history
+            .redo()
+            .unwrap()
+            .iter()
+            .for_each(|op| op.apply(&mut maze));
+        assert!(!maze.is_wall_present(&c, &b));
+        assert_eq!(maze.end(), b);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn recording_drops_redo_and_respects_limit() {
+        let (a, b) = (Point { x: 0, y: 0 }, Point { x: 1, y: 0 });
+        let mut history = History::with_limit(2);
+        history.record(Operation::RemoveWall(a, b));
+        history.record(Operation::AddWall(a, b));
+        history.record(Operation::RemoveWall(a, b));
+
+        assert_eq!(history.undo(), Some(Operation::AddWall(a, b)));
+        history.record(Operation::MoveStart { from: a, to: b });
+        assert!(!history.can_redo());
+
+        assert_eq!(
+            history.undo(),
+            Some(Operation::MoveStart { from: b, to: a })
+        );
+        assert_eq!(history.undo(), Some(Operation::RemoveWall(a, b)));
+        assert_eq!(history.undo(), None);
+        assert_eq!(
+            Operation::MoveStart { from: b, to: a }.reversed(),
+            Operation::MoveStart { from: a, to: b }
+        );
+    }
+
+    #[test]
+    fn zero_limit_records_nothing() {
+        let (a, b) = (Point { x: 0, y: 0 }, Point { x: 1, y: 0 });
+        let mut history = History::with_limit(0);
+        history.record(Operation::RemoveWall(a, b));
+        history.record(Operation::AddWall(a, b));
+
+        assert!(!history.can_undo());
+        assert_eq!(history.undo(), None);
+    }
+}
@@ -0,0 +1,272 @@
+use std::ops::Range;
+
+use crate::grid::Grid;
+use crate::maze::{Direction, Point};
+
+pub type Rectangle = [f64; 4];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pick {
+    Cell(Point),
+    Wall(Point, Point),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellLayout {
+    pub x_offset: f64,
+    pub y_offset: f64,
+    pub cell_size: f64,
+    pub grid: Grid,
+}
+
+impl Default for CellLayout {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl CellLayout {
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            x_offset: 0.0,
+            y_offset: 0.0,
+            cell_size,
+            grid: Grid::Square,
+        }
+    }
+
+    pub fn with_grid(grid: Grid, cell_size: f64) -> Self {
+        Self {
+            grid,
+            ..Self::new(cell_size)
+        }
+    }
+
+    pub fn wall_thickness(&self) -> f64 {
+        self.cell_size / 15.0
+    }
+
+    // Top left corner of the square around the circle inscribed in the cell of a `width` x
+    // `height` maze.
+    pub fn to_gui_coordinates(&self, point: &Point, width: usize, height: usize) -> (f64, f64) {
+        let [x, y] = self.grid.center(point, width, height);
+        let [x, y] = self.to_gui_point([x - 0.5, y - 0.5]);
+        (x, y)
+    }
+
+    // From grid coordinates, see `Grid`.
+    pub fn to_gui_point(&self, [x, y]: [f64; 2]) -> [f64; 2] {
+        [
+            self.x_offset + x * self.cell_size,
+            self.y_offset + y * self.cell_size,
+        ]
+    }
+
+    // Size of a `width` x `height` maze.
+    pub fn extent(&self, width: usize, height: usize) -> [f64; 2] {
+        let [width, height] = self.grid.extent(width, height);
+        [width * self.cell_size, height * self.cell_size]
+    }
+
+    // Inverse of `to_gui_coordinates`, `None` outside of a `width` x `height` maze.
+    pub fn from_gui_coordinates(
+        &self,
+        x: f64,
+        y: f64,
+        width: usize,
+        height: usize,
+    ) -> Option<Point> {
+        self.grid.locate(
+            (x - self.x_offset) / self.cell_size,
+            (y - self.y_offset) / self.cell_size,
+            width,
+            height,
+        )
+    }
+
+    // Positions close to the border shared by two cells pick the wall between them.
+    pub fn pick(&self, x: f64, y: f64, width: usize, height: usize) -> Option<Pick> {
+        let point = self.from_gui_coordinates(x, y, width, height)?;
+        if !self.grid.is_square() {
+            return Some(self.pick_side(point, x, y, width, height));
+        }
+        let (cell_x, cell_y) = self.to_gui_coordinates(&point, width, height);
+        let (dx, dy) = ((x - cell_x) / self.cell_size, (y - cell_y) / self.cell_size);
+        let tolerance = 0.2;
+
+        let (distance, direction) = [
+            (dy, Direction::North),
+            (1.0 - dy, Direction::South),
+            (1.0 - dx, Direction::East),
+            (dx, Direction::West),
+        ]
+        .iter()
+        .cloned()
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+        .unwrap();
+
+        if distance < tolerance {
+            let neighbor = point
+                .neighbors(width, height)
+                .into_iter()
+                .find(|n| point.relative_direction(n) == direction);
+            if let Some(neighbor) = neighbor {
+                return Some(Pick::Wall(point, neighbor));
+            }
+        }
+        Some(Pick::Cell(point))
+    }
+
+    fn pick_side(&self, point: Point, x: f64, y: f64, width: usize, height: usize) -> Pick {
+        let position = [
+            (x - self.x_offset) / self.cell_size,
+            (y - self.y_offset) / self.cell_size,
+        ];
+        let tolerance = 0.2;
+
+        self.grid
+            .directions(&point, width, height)
+            .iter()
+            .filter_map(|direction| {
+                let neighbor = self.grid.neighbor(&point, direction, width, height)?;
+                let distance = self
+                    .grid
+                    .side_segments(&point, direction, width, height)
+                    .into_iter()
+                    .map(|segment| distance_to_segment(position, segment))
+                    .fold(f64::INFINITY, f64::min);
+                Some((distance, neighbor))
+            })
+            .filter(|(distance, _)| *distance < tolerance)
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+            .map_or(Pick::Cell(point), |(_, neighbor)| {
+                Pick::Wall(point, neighbor)
+            })
+    }
+
+    // Columns and rows of a `width` x `height` maze intersecting a `view_width` x `view_height`
+    // window, so that cells out of sight are not drawn. Square grids only.
+    pub fn visible_cells(
+        &self,
+        view_width: f64,
+        view_height: f64,
+        width: usize,
+        height: usize,
+    ) -> (Range<usize>, Range<usize>) {
+        let range = |offset: f64, view: f64, limit: usize| {
+            let first = (-offset / self.cell_size).floor().max(0.0) as usize;
+            let last = ((view - offset) / self.cell_size).ceil().max(0.0) as usize;
+            first.min(limit)..last.min(limit)
+        };
+        (
+            range(self.x_offset, view_width, width),
+            range(self.y_offset, view_height, height),
+        )
+    }
+
+    // Part of the grid, in grid coordinates, seen through a `view_width` x `view_height`
+    // window, so that the cells of other grids out of sight are not drawn.
+    pub fn visible_bounds(&self, view_width: f64, view_height: f64) -> Rectangle {
+        [
+            -self.x_offset / self.cell_size,
+            -self.y_offset / self.cell_size,
+            view_width / self.cell_size,
+            view_height / self.cell_size,
+        ]
+    }
+
+    pub fn cell_rectangle(&self, point: &Point, width: usize, height: usize) -> Rectangle {
+        let (x, y) = self.to_gui_coordinates(point, width, height);
+        [x, y, self.cell_size, self.cell_size]
+    }
+}
+
+fn distance_to_segment([x, y]: [f64; 2], [x1, y1, x2, y2]: [f64; 4]) -> f64 {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let t = (((x - x1) * dx + (y - y1) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+    (x1 + t * dx - x).hypot(y1 + t * dy - y)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::layout::{CellLayout, Pick};
+    use crate::maze::Point;
+
+    #[test]
+    fn pick_cells_and_walls() {
+        let layout = CellLayout {
+            x_offset: 10.0,
+            y_offset: 0.0,
+            cell_size: 20.0,
+            grid: Grid::Square,
+        };
+
+        assert_eq!(
+            layout.pick(40.0, 30.0, 3, 2),
+            Some(Pick::Cell(Point { x: 1, y: 1 }))
+        );
+        assert_eq!(
+            layout.pick(49.0, 10.0, 3, 2),
+            Some(Pick::Wall(Point { x: 1, y: 0 }, Point { x: 2, y: 0 }))
+        );
+        assert_eq!(
+            layout.pick(20.0, 21.0, 3, 2),
+            Some(Pick::Wall(Point { x: 0, y: 1 }, Point { x: 0, y: 0 }))
+        );
+        // The outer border is not editable.
+        assert_eq!(
+            layout.pick(11.0, 10.0, 3, 2),
+            Some(Pick::Cell(Point { x: 0, y: 0 }))
+        );
+        assert_eq!(layout.pick(5.0, 10.0, 3, 2), None);
+        assert_eq!(layout.pick(20.0, 40.0, 3, 2), None);
+    }
+
+    #[test]
+    fn pick_hex_sides() {
+        let layout = CellLayout::with_grid(Grid::Hex, 10.0);
+        let [x, y] = Grid::Hex.center(&Point { x: 1, y: 1 }, 3, 3);
+        let (x, y) = (x * 10.0, y * 10.0);
+
+        assert_eq!(
+            layout.pick(x, y, 3, 3),
+            Some(Pick::Cell(Point { x: 1, y: 1 }))
+        );
+        // Half a cell to the left is on the west side.
+        assert_eq!(
+            layout.pick(x - 4.9, y, 3, 3),
+            Some(Pick::Wall(Point { x: 1, y: 1 }, Point { x: 0, y: 1 }))
+        );
+        assert_eq!(
+            layout.pick(x + 1.0, y - 5.0, 3, 3),
+            Some(Pick::Wall(Point { x: 1, y: 1 }, Point { x: 2, y: 0 }))
+        );
+    }
+
+    #[test]
+    fn visible_cells_are_clipped() {
+        let layout = CellLayout {
+            x_offset: -25.0,
+            y_offset: 30.0,
+            cell_size: 10.0,
+            grid: Grid::Square,
+        };
+
+        assert_eq!(layout.visible_cells(40.0, 50.0, 100, 100), (2..7, 0..2));
+        assert_eq!(layout.visible_cells(1000.0, 1000.0, 5, 5), (2..5, 0..5));
+        assert_eq!(layout.visible_cells(40.0, 20.0, 100, 100), (2..7, 0..0));
+    }
+
+    #[test]
+    fn visible_bounds_are_in_grid_coordinates() {
+        let layout = CellLayout {
+            x_offset: -25.0,
+            y_offset: 30.0,
+            cell_size: 10.0,
+            grid: Grid::Hex,
+        };
+
+        assert_eq!(layout.visible_bounds(40.0, 50.0), [2.5, -3.0, 4.0, 5.0]);
+    }
+}
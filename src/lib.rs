@@ -1,3 +1,22 @@
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod code;
+pub mod color;
+pub mod generation;
+pub mod graph;
+pub mod grid;
+pub mod history;
+pub mod layout;
 pub mod maze;
+pub mod play;
+pub mod race;
+pub mod raster;
+pub mod recording;
+pub mod render;
+pub mod scene;
+pub mod session;
 pub mod solving;
-pub mod generation;
\ No newline at end of file
+pub mod stats;
+pub mod svg;
+pub mod terminal;
+pub mod theme;
@@ -1,14 +1,47 @@
+#[cfg(feature = "gui")]
 use glutin_window::GlutinWindow as Window;
-use opengl_graphics::{GlGraphics, OpenGL};
+#[cfg(feature = "gui")]
+use opengl_graphics::{GlGraphics, GlyphCache, OpenGL, TextureSettings};
+#[cfg(feature = "gui")]
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{ButtonEvent, RenderEvent, UpdateEvent};
+#[cfg(feature = "gui")]
+use piston::input::{ButtonEvent, MouseCursorEvent, MouseScrollEvent, RenderEvent, UpdateEvent};
+#[cfg(feature = "gui")]
 use piston::window;
+#[cfg(feature = "gui")]
 use piston::window::WindowSettings;
 
+use maze::cli;
+
+#[cfg(feature = "gui")]
 mod app;
+#[cfg(feature = "gui")]
+mod gl_renderer;
+
+#[cfg(feature = "gui")]
+const FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
 
 fn main() {
-    let resolution = window::Size::from((1200, 600));
+    match cli::parse() {
+        #[cfg(feature = "gui")]
+        cli::Command::Gui(options) => run_gui(&options),
+        #[cfg(not(feature = "gui"))]
+        cli::Command::Gui(_) => {
+            eprintln!("error: maze was built without the gui feature, use a subcommand");
+            std::process::exit(1);
+        }
+        command => {
+            if let Err(error) = cli::run(command) {
+                eprintln!("error: {}", error);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn run_gui(options: &cli::GuiOptions) {
+    let resolution = window::Size::from((options.window_width, options.window_height));
     let opengl = OpenGL::V3_2;
 
     // Create a window
@@ -19,7 +52,10 @@ fn main() {
         .build()
         .unwrap();
 
-    let mut app = app::App::new(GlGraphics::new(opengl), resolution);
+    let mut gl = GlGraphics::new(opengl);
+    let mut glyphs = GlyphCache::from_bytes(FONT, (), TextureSettings::new())
+        .expect("failed to load the HUD font");
+    let mut app = app::App::new(resolution, options);
 
     // Event loop
     let mut events = Events::new(EventSettings::new());
@@ -28,7 +64,17 @@ fn main() {
         // Catch the events of the keyboard
 
         if let Some(args) = event.render_args() {
-            app.render(&args);
+            gl.draw(args.viewport(), |c, gl| {
+                app.render(&mut gl_renderer::GlRenderer::new(c, gl, &mut glyphs));
+            });
+        }
+
+        if let Some(position) = event.mouse_cursor_args() {
+            app.mouse_move(position);
+        }
+
+        if let Some(scroll) = event.mouse_scroll_args() {
+            app.mouse_scroll(scroll);
         }
 
         if let Some(args) = event.button_args() {
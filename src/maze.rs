@@ -1,12 +1,31 @@
 use std::cmp::Ordering;
 use std::collections::HashSet;
+use std::convert::TryFrom;
 
-#[derive(Hash, Eq, PartialEq, Debug, Clone)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::grid::Grid;
+
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Direction {
     North,
     South,
     East,
     West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+    Inward,
+    Outward,
+    Clockwise,
+    CounterClockwise,
+    InwardClockwise,
+    InwardCounterClockwise,
+    OutwardClockwise,
+    OutwardCounterClockwise,
 }
 
 impl Direction {
@@ -16,11 +35,24 @@ impl Direction {
             Direction::South => Direction::North,
             Direction::East => Direction::West,
             Direction::West => Direction::East,
+            Direction::NorthEast => Direction::SouthWest,
+            Direction::NorthWest => Direction::SouthEast,
+            Direction::SouthEast => Direction::NorthWest,
+            Direction::SouthWest => Direction::NorthEast,
+            Direction::Inward => Direction::Outward,
+            Direction::Outward => Direction::Inward,
+            Direction::Clockwise => Direction::CounterClockwise,
+            Direction::CounterClockwise => Direction::Clockwise,
+            Direction::InwardClockwise => Direction::OutwardCounterClockwise,
+            Direction::InwardCounterClockwise => Direction::OutwardClockwise,
+            Direction::OutwardClockwise => Direction::InwardCounterClockwise,
+            Direction::OutwardCounterClockwise => Direction::InwardClockwise,
         }
     }
 }
 
 #[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Point {
     pub x: usize,
     pub y: usize,
@@ -48,6 +80,18 @@ impl Point {
         neighbors
     }
 
+    pub fn neighbor(&self, direction: &Direction, x_limit: usize, y_limit: usize) -> Option<Point> {
+        let (x, y) = (self.x, self.y);
+        let (n_x, n_y) = match direction {
+            Direction::North if y > 0 => (x, y - 1),
+            Direction::South if y + 1 < y_limit => (x, y + 1),
+            Direction::East if x + 1 < x_limit => (x + 1, y),
+            Direction::West if x > 0 => (x - 1, y),
+            _ => return None,
+        };
+        Some(Point { x: n_x, y: n_y })
+    }
+
     pub fn relative_direction(&self, other: &Point) -> Direction {
         match other.x.cmp(&self.x) {
             Ordering::Greater => Direction::East,
@@ -67,8 +111,10 @@ impl Point {
 }
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Cell {
     pub position: Point,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_walls"))]
     walls: HashSet<Direction>,
 }
 
@@ -102,12 +148,37 @@ impl Cell {
     pub fn walls_mut(&mut self) -> &mut HashSet<Direction> {
         &mut self.walls
     }
+
+    // Walls in the order of `Direction` rather than the set's hash order: north, south,
+    // east, west, the diagonals, then inward, outward, clockwise, counter-clockwise and the
+    // inward and outward halves of polar grids.
+    pub fn sorted_walls(&self) -> Vec<Direction> {
+        let mut walls: Vec<Direction> = self.walls.iter().cloned().collect();
+        walls.sort();
+        walls
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_walls<S: serde::Serializer>(
+    walls: &HashSet<Direction>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut walls: Vec<&Direction> = walls.iter().collect();
+    walls.sort();
+    serializer.collect_seq(walls)
 }
 
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(into = "MazeRepr", try_from = "MazeRepr")
+)]
 pub struct Maze {
     pub width: usize,
     pub height: usize,
+    grid: Grid,
     cells: Vec<Vec<Cell>>,
     start: Point,
     end: Point,
@@ -116,22 +187,36 @@ pub struct Maze {
 #[allow(dead_code)]
 impl Maze {
     pub fn new(width: usize, height: usize) -> Self {
+        Self::with_grid(Grid::Square, width, height)
+    }
+
+    pub fn with_grid(grid: Grid, width: usize, height: usize) -> Self {
         let start = Point { x: 0, y: 0 };
         let end = Point {
             x: width - 1,
             y: height - 1,
         };
-        let mut cells = vec![vec![Cell::new(0, 0); height]; width];
+        // The rings of polar grids get wider outward: the cells past the end of the inner
+        // ones are unused and have no walls.
+        let columns = grid.row_width(height - 1, width);
+        let mut cells = vec![vec![Cell::new(0, 0); height]; columns];
 
         for (x, row) in cells.iter_mut().enumerate() {
             for (y, cell) in row.iter_mut().enumerate() {
                 cell.position = Point { x, y };
+                cell.walls = if x < grid.row_width(y, width) {
+                    let directions = grid.directions(&cell.position, width, height);
+                    directions.iter().cloned().collect()
+                } else {
+                    HashSet::new()
+                };
             }
         }
 
         Self {
             width,
             height,
+            grid,
             cells,
             start,
             end,
@@ -166,8 +251,233 @@ impl Maze {
         &self.cells
     }
 
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    pub fn row_width(&self, y: usize) -> usize {
+        self.grid.row_width(y, self.width)
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point.y < self.height && point.x < self.row_width(point.y)
+    }
+
+    // Every cell in row-major order.
+    pub fn points(&self) -> Vec<Point> {
+        (0..self.height)
+            .flat_map(|y| (0..self.row_width(y)).map(move |x| Point { x, y }))
+            .collect()
+    }
+
+    pub fn directions(&self, point: &Point) -> &'static [Direction] {
+        self.grid.directions(point, self.width, self.height)
+    }
+
+    pub fn neighbors(&self, point: &Point) -> Vec<Point> {
+        self.grid.neighbors(point, self.width, self.height)
+    }
+
+    pub fn neighbor(&self, point: &Point, direction: &Direction) -> Option<Point> {
+        self.grid
+            .neighbor(point, direction, self.width, self.height)
+    }
+
+    pub fn distance(&self, p1: &Point, p2: &Point) -> usize {
+        self.grid.distance(p1, p2)
+    }
+
+    // Side of `p1` shared with its neighbor `p2`.
+    pub fn direction_between(&self, p1: &Point, p2: &Point) -> Direction {
+        if self.grid.is_square() {
+            return p1.relative_direction(p2);
+        }
+        self.directions(p1)
+            .iter()
+            .find(|direction| self.neighbor(p1, direction) == Some(*p2))
+            .cloned()
+            .unwrap_or_else(|| panic!("{:?} and {:?} are not neighbors", p1, p2))
+    }
+
+    pub fn remove_wall_between(&mut self, p1: &Point, p2: &Point) {
+        let direction = self.direction_between(p1, p2);
+        self.cell_mut(p2).remove_wall(&direction.opposite());
+        self.cell_mut(p1).remove_wall(&direction);
+    }
+
+    pub fn add_wall_between(&mut self, p1: &Point, p2: &Point) {
+        let direction = self.direction_between(p1, p2);
+        self.cell_mut(p2).add_wall(direction.opposite());
+        self.cell_mut(p1).add_wall(direction);
+    }
+
     pub fn is_wall_present(&self, p1: &Point, p2: &Point) -> bool {
-        let direction = p1.relative_direction(&p2);
+        let direction = self.direction_between(p1, p2);
         self.cell(p1).walls.contains(&direction)
     }
+
+    // Wall on the west side of column `x` (0..=width) in row `y`, square grids only.
+    pub fn vertical_wall(&self, x: usize, y: usize) -> bool {
+        if x < self.width {
+            self.cell(&Point { x, y }).walls.contains(&Direction::West)
+        } else {
+            self.cell(&Point { x: x - 1, y })
+                .walls
+                .contains(&Direction::East)
+        }
+    }
+
+    // Wall on the north side of row `y` (0..=height) in column `x`, square grids only.
+    pub fn horizontal_wall(&self, x: usize, y: usize) -> bool {
+        if y < self.height {
+            self.cell(&Point { x, y }).walls.contains(&Direction::North)
+        } else {
+            self.cell(&Point { x, y: y - 1 })
+                .walls
+                .contains(&Direction::South)
+        }
+    }
+}
+
+// Stable external representation of a maze, see `docs/json.md`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MazeRepr {
+    // Left out for square grids.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Grid::is_square")
+    )]
+    pub grid: Grid,
+    pub width: usize,
+    pub height: usize,
+    pub start: Point,
+    pub end: Point,
+    // Row-major, see `Maze::points`: except on polar grids, the walls of cell (x, y) are at
+    // index `y * width + x`.
+    pub walls: Vec<Vec<Direction>>,
+}
+
+impl From<Maze> for MazeRepr {
+    fn from(maze: Maze) -> Self {
+        let walls = maze
+            .points()
+            .iter()
+            .map(|point| maze.cell(point).sorted_walls())
+            .collect();
+
+        Self {
+            grid: maze.grid,
+            width: maze.width,
+            height: maze.height,
+            start: maze.start,
+            end: maze.end,
+            walls,
+        }
+    }
+}
+
+impl TryFrom<MazeRepr> for Maze {
+    type Error = String;
+
+    fn try_from(repr: MazeRepr) -> Result<Self, Self::Error> {
+        if repr.width == 0 || repr.height == 0 {
+            return Err("maze dimensions must be positive".to_string());
+        }
+        // Every row and column has a cell, which bounds the dimensions by the number of
+        // cells given before anything is computed from them.
+        let cells = repr.walls.len();
+        let longest = repr.width.max(repr.height);
+        if longest > cells {
+            return Err(format!(
+                "expected at least {} cells, found {}",
+                longest, cells
+            ));
+        }
+        match repr.grid.cell_count(repr.width, repr.height) {
+            Some(expected) if expected == cells => {}
+            Some(expected) => {
+                return Err(format!("expected {} cells, found {}", expected, cells));
+            }
+            None => return Err("maze dimensions are too large".to_string()),
+        }
+
+        let mut maze = Maze::with_grid(repr.grid, repr.width, repr.height);
+        let points = maze.points();
+        for (name, point) in [("start", repr.start), ("end", repr.end)] {
+            if !maze.contains(&point) {
+                return Err(format!("{} {:?} is outside of the maze", name, point));
+            }
+        }
+
+        maze.start = repr.start;
+        maze.end = repr.end;
+        for (position, walls) in points.iter().zip(repr.walls) {
+            if let Some(direction) = walls
+                .iter()
+                .find(|direction| !maze.directions(position).contains(direction))
+            {
+                return Err(format!(
+                    "{:?} has a {:?} wall, which {} grids do not have",
+                    position, direction, maze.grid
+                ));
+            }
+            maze.cell_mut(position).walls = walls.into_iter().collect();
+        }
+
+        for point in points {
+            for neighbor in maze.neighbors(&point) {
+                if maze.is_wall_present(&point, &neighbor)
+                    != maze.is_wall_present(&neighbor, &point)
+                {
+                    return Err(format!(
+                        "walls between {:?} and {:?} do not match",
+                        point, neighbor
+                    ));
+                }
+            }
+        }
+        Ok(maze)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::maze::{Direction, Maze, Point};
+
+    #[test]
+    fn json_round_trip() {
+        let mut maze = Maze::new(2, 1);
+        maze.cell_mut(&Point { x: 0, y: 0 })
+            .remove_wall(&Direction::East);
+        maze.cell_mut(&Point { x: 1, y: 0 })
+            .remove_wall(&Direction::West);
+
+        let json = serde_json::to_string(&maze).unwrap();
+        assert_eq!(
+            json,
+            r#"{"width":2,"height":1,"start":{"x":0,"y":0},"end":{"x":1,"y":0},"walls":[["North","South","West"],["North","South","East"]]}"#
+        );
+
+        let decoded: Maze = serde_json::from_str(&json).unwrap();
+        assert!(!decoded.is_wall_present(&Point { x: 0, y: 0 }, &Point { x: 1, y: 0 }));
+        assert_eq!(decoded.end(), Point { x: 1, y: 0 });
+    }
+
+    #[test]
+    fn rejects_wrong_cell_counts() {
+        let json = r#"{"width":100000,"height":100000,"start":{"x":0,"y":0},"end":{"x":0,"y":0},"walls":[]}"#;
+        assert!(serde_json::from_str::<Maze>(json).is_err());
+
+        let json = r#"{"width":2,"height":1,"start":{"x":0,"y":0},"end":{"x":1,"y":0},"walls":[["North","South","East","West"]]}"#;
+        let error = serde_json::from_str::<Maze>(json).err().unwrap();
+        assert!(error
+            .to_string()
+            .contains("expected at least 2 cells, found 1"));
+    }
+
+    #[test]
+    fn rejects_one_sided_walls() {
+        let json = r#"{"width":2,"height":1,"start":{"x":0,"y":0},"end":{"x":1,"y":0},"walls":[["North","South","West"],["North","South","East","West"]]}"#;
+        assert!(serde_json::from_str::<Maze>(json).is_err());
+    }
 }
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use crate::maze::{Direction, Maze, Point};
+use crate::solving::{AStarSolver, Solver};
+
+pub struct Game {
+    position: Point,
+    end: Point,
+    trail: Vec<Point>,
+    moves: usize,
+    elapsed: f64,
+    optimal: Option<usize>,
+}
+
+impl Game {
+    pub fn new(maze: &Maze) -> Self {
+        // The path includes both the start and the end.
+        let optimal = AStarSolver::new(maze).solve().map(|path| path.len() - 1);
+
+        Self {
+            position: maze.start(),
+            end: maze.end(),
+            trail: vec![maze.start()],
+            moves: 0,
+            elapsed: 0.0,
+            optimal,
+        }
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    // Every cell the player went through, in order, including the current one.
+    pub fn trail(&self) -> &[Point] {
+        &self.trail
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    pub fn optimal(&self) -> Option<usize> {
+        self.optimal
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position == self.end
+    }
+
+    // Returns `false` when a wall or the border is in the way.
+    pub fn try_move(&mut self, maze: &Maze, direction: Direction) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let next = match maze.neighbor(&self.position, &direction) {
+            Some(next) if !maze.is_wall_present(&self.position, &next) => next,
+            _ => return false,
+        };
+
+        self.position = next;
+        self.trail.push(next);
+        self.moves += 1;
+        true
+    }
+
+    // The clock starts with the first move and stops at the end.
+    pub fn update(&mut self, dt: f64) {
+        if self.moves > 0 && !self.is_finished() {
+            self.elapsed += dt;
+        }
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} moves in {:.1}s", self.moves, self.elapsed)?;
+        if let Some(optimal) = self.optimal {
+            write!(f, ", the shortest path takes {} moves", optimal)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::{Direction, Maze, Point};
+    use crate::play::Game;
+
+    #[test]
+    fn walls_block_the_player() {
+        // S . |
+        // ----+ .
+        //     E .
+        let mut maze = Maze::new(3, 2);
+        maze.set_end(1, 1);
+        maze.remove_wall_between(&Point { x: 0, y: 0 }, &Point { x: 1, y: 0 });
+        maze.remove_wall_between(&Point { x: 1, y: 0 }, &Point { x: 1, y: 1 });
+
+        let mut game = Game::new(&maze);
+        assert_eq!(game.optimal(), Some(2));
+
+        assert!(!game.try_move(&maze, Direction::North));
+        assert!(!game.try_move(&maze, Direction::South));
+        game.update(1.0);
+        assert!(game.try_move(&maze, Direction::East));
+        game.update(0.5);
+        assert!(!game.try_move(&maze, Direction::East));
+        assert!(game.try_move(&maze, Direction::South));
+        game.update(0.5);
+
+        assert!(game.is_finished());
+        assert!(!game.try_move(&maze, Direction::North));
+        assert_eq!(game.moves(), 2);
+        assert_eq!(game.elapsed(), 0.5);
+        assert_eq!(
+            game.trail(),
+            &[
+                Point { x: 0, y: 0 },
+                Point { x: 1, y: 0 },
+                Point { x: 1, y: 1 }
+            ]
+        );
+    }
+}
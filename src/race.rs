@@ -0,0 +1,139 @@
+use crate::maze::{Maze, Point};
+use crate::solving::{Path, SolverKind};
+
+// A solver taking part in a race. It is run to the end up front and replayed one expanded
+// cell per step, so the lanes do not need to borrow the maze while the race is shown.
+pub struct Lane {
+    kind: SolverKind,
+    expanded: Vec<Point>,
+    path: Option<Path>,
+}
+
+impl Lane {
+    pub fn new(maze: &Maze, kind: SolverKind) -> Self {
+        let mut solver = kind.build(maze);
+        let mut expanded = Vec::new();
+        let mut path = None;
+
+        while path.is_none() && !solver.is_done() {
+            path = solver.next_step();
+            // The last step of an exhausted search does not expand anything.
+            if solver.nodes_expanded() > expanded.len() {
+                expanded.extend(solver.last_expanded());
+            }
+        }
+
+        Self {
+            kind,
+            expanded,
+            path,
+        }
+    }
+
+    pub fn kind(&self) -> SolverKind {
+        self.kind
+    }
+
+    // Cells expanded after `step` steps, in order.
+    pub fn expanded(&self, step: usize) -> &[Point] {
+        &self.expanded[..step.min(self.expanded.len())]
+    }
+
+    pub fn nodes_expanded(&self, step: usize) -> usize {
+        self.expanded(step).len()
+    }
+
+    pub fn is_finished(&self, step: usize) -> bool {
+        step >= self.expanded.len()
+    }
+
+    // Only known to the viewers once the lane is finished.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_ref()
+    }
+}
+
+pub struct Race {
+    lanes: Vec<Lane>,
+    step: usize,
+}
+
+impl Race {
+    pub fn new(maze: &Maze, kinds: &[SolverKind]) -> Self {
+        Self {
+            lanes: kinds.iter().map(|&kind| Lane::new(maze, kind)).collect(),
+            step: 0,
+        }
+    }
+
+    pub fn lanes(&self) -> &[Lane] {
+        &self.lanes
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.lanes.iter().all(|lane| lane.is_finished(self.step))
+    }
+
+    // Advances every lane by one step and returns the cell each of them expanded, if any.
+    pub fn next_step(&mut self) -> Vec<Option<Point>> {
+        if self.is_finished() {
+            return vec![None; self.lanes.len()];
+        }
+        let step = self.step;
+        self.step += 1;
+        self.lanes
+            .iter()
+            .map(|lane| lane.expanded.get(step).copied())
+            .collect()
+    }
+
+    pub fn restart(&mut self) {
+        self.step = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::Maze;
+    use crate::race::Race;
+    use crate::solving::{AStarSolver, Solver, SolverKind};
+
+    #[test]
+    fn lanes_finish_at_their_own_pace() {
+        let mut maze = Maze::new(4, 1);
+        for x in 0..3 {
+            maze.remove_wall_between(&(x, 0).into(), &(x + 1, 0).into());
+        }
+        maze.set_start(1, 0);
+        maze.set_end(3, 0);
+
+        let mut race = Race::new(&maze, &[SolverKind::AStar, SolverKind::Bfs]);
+        let expected = AStarSolver::new(&maze).solve();
+
+        // A* goes straight to the end, BFS also expands the cell behind the start.
+        assert_eq!(race.next_step(), vec![Some((1, 0).into()); 2]);
+        assert_eq!(
+            race.next_step(),
+            vec![Some((2, 0).into()), Some((0, 0).into())]
+        );
+        assert_eq!(
+            race.next_step(),
+            vec![Some((3, 0).into()), Some((2, 0).into())]
+        );
+        assert!(race.lanes()[0].is_finished(race.step()));
+        assert!(!race.is_finished());
+        assert_eq!(race.next_step(), vec![None, Some((3, 0).into())]);
+        assert!(race.is_finished());
+
+        for lane in race.lanes() {
+            assert_eq!(lane.path(), expected.as_ref());
+        }
+        assert_eq!(race.lanes()[1].nodes_expanded(race.step()), 4);
+        race.restart();
+        assert_eq!(race.lanes()[1].nodes_expanded(race.step()), 0);
+    }
+}
@@ -0,0 +1,331 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::color::{self, Color, ColorMap};
+use crate::layout::Rectangle;
+use crate::maze::Maze;
+use crate::render::{MazeDrawer, Renderer};
+use crate::scene::Scene;
+use crate::theme::Theme;
+
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // RGBA, row-major, 4 bytes per pixel.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        let [r, g, b] = color::to_rgb8(color);
+        let a = (color[3].clamp(0.0, 1.0) * 255.0).round() as u8;
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[r, g, b, a]);
+        }
+    }
+
+    // Pixels are covered when their center lies inside the rectangle, which is what
+    // OpenGL rasterization does for the same geometry.
+    pub fn fill_rect(&mut self, color: Color, [x, y, w, h]: Rectangle) {
+        let x_start = (x - 0.5).ceil().max(0.0) as usize;
+        let y_start = (y - 0.5).ceil().max(0.0) as usize;
+        let x_end = ((x + w - 0.5).ceil().max(0.0) as usize).min(self.width);
+        let y_end = ((y + h - 0.5).ceil().max(0.0) as usize).min(self.height);
+
+        for py in y_start..y_end {
+            for px in x_start..x_end {
+                self.blend(px, py, color);
+            }
+        }
+    }
+
+    // Covers the pixels whose center is closer to the segment than half the thickness.
+    pub fn fill_line(&mut self, color: Color, thickness: f64, [x1, y1, x2, y2]: [f64; 4]) {
+        let radius = thickness / 2.0;
+        let x_start = (x1.min(x2) - radius - 0.5).ceil().max(0.0) as usize;
+        let y_start = (y1.min(y2) - radius - 0.5).ceil().max(0.0) as usize;
+        let x_end = ((x1.max(x2) + radius - 0.5).ceil().max(0.0) as usize).min(self.width);
+        let y_end = ((y1.max(y2) + radius - 0.5).ceil().max(0.0) as usize).min(self.height);
+
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let length_squared = dx * dx + dy * dy;
+        for py in y_start..y_end {
+            for px in x_start..x_end {
+                let (cx, cy) = (px as f64 + 0.5, py as f64 + 0.5);
+                let t = if length_squared == 0.0 {
+                    0.0
+                } else {
+                    (((cx - x1) * dx + (cy - y1) * dy) / length_squared).clamp(0.0, 1.0)
+                };
+                let (ex, ey) = (x1 + t * dx - cx, y1 + t * dy - cy);
+                if ex * ex + ey * ey <= radius * radius {
+                    self.blend(px, py, color);
+                }
+            }
+        }
+    }
+
+    // Covers the pixels whose center is inside the polygon, using the even-odd rule.
+    pub fn fill_polygon(&mut self, color: Color, corners: &[[f64; 2]]) {
+        let rows = corners.iter().map(|corner| corner[1]);
+        let y_min = rows.clone().fold(f64::INFINITY, f64::min);
+        let y_max = rows.fold(f64::NEG_INFINITY, f64::max);
+        let y_start = (y_min - 0.5).ceil().max(0.0) as usize;
+        let y_end = ((y_max - 0.5).ceil().max(0.0) as usize).min(self.height);
+
+        for py in y_start..y_end {
+            let cy = py as f64 + 0.5;
+            let mut crossings: Vec<f64> = (0..corners.len())
+                .filter_map(|i| {
+                    let [x1, y1] = corners[i];
+                    let [x2, y2] = corners[(i + 1) % corners.len()];
+                    if (y1 <= cy) == (y2 <= cy) {
+                        return None;
+                    }
+                    Some(x1 + (cy - y1) / (y2 - y1) * (x2 - x1))
+                })
+                .collect();
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            for span in crossings.chunks_exact(2) {
+                let x_start = (span[0] - 0.5).ceil().max(0.0) as usize;
+                let x_end = ((span[1] - 0.5).ceil().max(0.0) as usize).min(self.width);
+                for px in x_start..x_end {
+                    self.blend(px, py, color);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: usize, y: usize, color: Color) {
+        let [r, g, b] = color::to_rgb8(color);
+        let alpha = color[3].clamp(0.0, 1.0);
+        let i = (y * self.width + x) * 4;
+        let pixel = &mut self.pixels[i..i + 4];
+        for (channel, source) in pixel.iter_mut().zip(&[r, g, b]) {
+            *channel = (*source as f32 * alpha + *channel as f32 * (1.0 - alpha)).round() as u8;
+        }
+        pixel[3] = ((alpha + pixel[3] as f32 / 255.0 * (1.0 - alpha)) * 255.0).round() as u8;
+    }
+
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(to_io_error)?;
+        writer.write_image_data(&self.pixels).map_err(to_io_error)
+    }
+
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, file: P) -> io::Result<()> {
+        self.write_png(BufWriter::new(File::create(file)?))
+    }
+}
+
+impl Renderer for Canvas {
+    fn view_size(&self) -> [f64; 2] {
+        [self.width as f64, self.height as f64]
+    }
+
+    fn clear(&mut self, color: Color) {
+        Canvas::clear(self, color);
+    }
+
+    fn draw_rect(&mut self, color: Color, rect: Rectangle) {
+        self.fill_rect(color, rect);
+    }
+
+    fn draw_line(&mut self, color: Color, thickness: f64, line: [f64; 4]) {
+        self.fill_line(color, thickness, line);
+    }
+
+    fn draw_polygon(&mut self, color: Color, corners: &[[f64; 2]]) {
+        self.fill_polygon(color, corners);
+    }
+}
+
+fn to_io_error(error: png::EncodingError) -> io::Error {
+    match error {
+        png::EncodingError::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::Other, error),
+    }
+}
+
+pub struct PngWriter {
+    cell_size: f64,
+    theme: Theme,
+}
+
+impl Default for PngWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PngWriter {
+    pub fn new() -> Self {
+        Self {
+            cell_size: 20.0,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        self.theme = *theme;
+        self
+    }
+
+    pub fn back_color(mut self, color: Color) -> Self {
+        self.theme.back = color;
+        self
+    }
+
+    pub fn wall_color(mut self, color: Color) -> Self {
+        self.theme.wall = color;
+        self
+    }
+
+    // Walls on the border of other grids than the square one would be cut in half without
+    // a margin.
+    fn margin(&self, maze: &Maze) -> f64 {
+        if maze.grid().is_square() {
+            0.0
+        } else {
+            self.cell_size / 15.0
+        }
+    }
+
+    pub fn image_size(&self, maze: &Maze) -> (usize, usize) {
+        let margin = self.margin(maze);
+        let [width, height] = maze.grid().extent(maze.width, maze.height);
+        (
+            (width * self.cell_size + 2.0 * margin).ceil() as usize,
+            (height * self.cell_size + 2.0 * margin).ceil() as usize,
+        )
+    }
+
+    // Without a color map, only the start and end cells are highlighted.
+    pub fn render(&self, maze: &Maze, color_map: Option<&ColorMap>) -> Canvas {
+        let (width, height) = self.image_size(maze);
+        let mut canvas = Canvas::new(width, height);
+        canvas.clear(self.theme.back);
+
+        let mut scene = Scene::with_grid(maze.grid(), maze.width, maze.height);
+        scene.map_colors(|point, _| match color_map {
+            Some(color_map) => color_map.get(&point).copied().flatten(),
+            None if point == maze.start() => Some(self.theme.start),
+            None if point == maze.end() => Some(self.theme.end),
+            None => None,
+        });
+        scene.update(maze);
+
+        let margin = self.margin(maze);
+        MazeDrawer::new()
+            .grid(maze.grid())
+            .x_offset(margin)
+            .y_offset(margin)
+            .cell_size(self.cell_size)
+            .wall_color(self.theme.wall)
+            .draw_maze(&mut canvas, &scene);
+        canvas
+    }
+
+    pub fn save<P: AsRef<std::path::Path>>(
+        &self,
+        maze: &Maze,
+        color_map: Option<&ColorMap>,
+        file: P,
+    ) -> io::Result<()> {
+        self.render(maze, color_map).save_png(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{BACK_COLOR, START_COLOR, WALL_COLOR};
+    use crate::maze::Maze;
+    use crate::raster::{Canvas, PngWriter};
+
+    #[test]
+    fn fill_rect_covers_pixel_centers() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_rect([1.0, 0.0, 0.0, 1.0], [0.6, 0.4, 2.0, 1.0]);
+
+        assert_eq!(canvas.pixel(0, 0), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(1, 0), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(2, 0), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(3, 0), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(1, 1), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn fill_line_covers_a_thick_segment() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.fill_line([1.0, 0.0, 0.0, 1.0], 1.0, [0.5, 0.5, 4.5, 4.5]);
+
+        assert_eq!(canvas.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(2, 2), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(4, 4), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(3, 1), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn fill_polygon_covers_pixel_centers() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.fill_polygon([1.0, 0.0, 0.0, 1.0], &[[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]]);
+
+        assert_eq!(canvas.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(2, 1), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(1, 1), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(0, 3), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn render_marks_walls_and_start() {
+        let maze = Maze::new(2, 2);
+        let canvas = PngWriter::new().cell_size(30.0).render(&maze, None);
+
+        let to_rgba = |c: [f32; 4]| {
+            let [r, g, b] = crate::color::to_rgb8(c);
+            [r, g, b, 255]
+        };
+        assert_eq!((canvas.width(), canvas.height()), (60, 60));
+        assert_eq!(canvas.pixel(0, 0), to_rgba(WALL_COLOR));
+        assert_eq!(canvas.pixel(15, 15), to_rgba(START_COLOR));
+        assert_eq!(canvas.pixel(15, 45), to_rgba(BACK_COLOR));
+    }
+}
@@ -0,0 +1,339 @@
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use crate::color::{Color, ColorMap};
+use crate::generation::{CellState, Generator};
+use crate::maze::{Maze, Point};
+use crate::raster::{Canvas, PngWriter};
+use crate::solving::Solver;
+use crate::theme::Theme;
+
+pub struct GifRecorder {
+    cell_size: f64,
+    theme: Theme,
+    frame_skip: usize,
+    frame_delay: Duration,
+    final_delay: Duration,
+}
+
+impl Default for GifRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GifRecorder {
+    pub fn new() -> Self {
+        Self {
+            cell_size: 10.0,
+            theme: Theme::default(),
+            frame_skip: 1,
+            frame_delay: Duration::from_millis(20),
+            final_delay: Duration::from_secs(2),
+        }
+    }
+
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        self.theme = *theme;
+        self
+    }
+
+    pub fn back_color(mut self, color: Color) -> Self {
+        self.theme.back = color;
+        self
+    }
+
+    pub fn wall_color(mut self, color: Color) -> Self {
+        self.theme.wall = color;
+        self
+    }
+
+    // Only every `frame_skip`-th step becomes a frame; the last step is always kept.
+    pub fn frame_skip(mut self, frame_skip: usize) -> Self {
+        self.frame_skip = frame_skip.max(1);
+        self
+    }
+
+    // GIF delays have a resolution of 10ms.
+    pub fn frame_delay(mut self, frame_delay: Duration) -> Self {
+        self.frame_delay = frame_delay;
+        self
+    }
+
+    pub fn final_delay(mut self, final_delay: Duration) -> Self {
+        self.final_delay = final_delay;
+        self
+    }
+
+    pub fn record_generation<W: Write>(
+        &self,
+        generator: &mut dyn Generator,
+        writer: W,
+    ) -> io::Result<()> {
+        let mut color_map = self.initial_color_map(generator.maze());
+        let (start, end) = {
+            let maze = generator.maze();
+            (maze.start(), maze.end())
+        };
+        let mut encoder = self.encoder(generator.maze(), writer)?;
+
+        let mut step = 0;
+        while !generator.is_done() {
+            for (point, state) in generator.next_step().cells {
+                if point != start && point != end {
+                    let color = match state {
+                        CellState::Unvisited => None,
+                        CellState::Visited => Some(self.theme.visited),
+                        CellState::Current => Some(self.theme.current),
+                    };
+                    color_map.insert(point, color);
+                }
+            }
+            step += 1;
+
+            if generator.is_done() {
+                // The last step only marks the final cell as visited.
+                self.write_frame(&mut encoder, generator.maze(), &color_map, true)?;
+            } else if step % self.frame_skip == 0 {
+                self.write_frame(&mut encoder, generator.maze(), &color_map, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    // `solver` solves `maze`.
+    pub fn record_solving<W: Write>(
+        &self,
+        maze: &Maze,
+        solver: &mut dyn Solver,
+        writer: W,
+    ) -> io::Result<()> {
+        let mut color_map = self.initial_color_map(maze);
+        let mut encoder = self.encoder(maze, writer)?;
+        let is_marker = |point: &Point| *point == maze.start() || *point == maze.end();
+
+        let mut step = 0;
+        let mut current = None;
+        loop {
+            let path = solver.next_step();
+            step += 1;
+
+            // The cell expanded last stands out until the next one is.
+            if let Some(point) = current.take() {
+                color_map.insert(point, Some(self.theme.visited));
+            }
+            if let Some(point) = solver.last_expanded().filter(|p| !is_marker(p)) {
+                color_map.insert(point, Some(self.theme.current));
+                current = Some(point);
+            }
+
+            if let Some(path) = &path {
+                for point in path.iter().filter(|p| !is_marker(p)) {
+                    color_map.insert(*point, Some(self.theme.path));
+                }
+            }
+
+            let done = path.is_some() || solver.is_done();
+            if done || step % self.frame_skip == 0 {
+                self.write_frame(&mut encoder, maze, &color_map, done)?;
+            }
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    fn initial_color_map(&self, maze: &Maze) -> ColorMap {
+        let mut color_map = ColorMap::with_capacity(maze.width * maze.height);
+        for point in maze.points() {
+            color_map.insert(point, None);
+        }
+        color_map.insert(maze.start(), Some(self.theme.start));
+        color_map.insert(maze.end(), Some(self.theme.end));
+        color_map
+    }
+
+    fn encoder<W: Write>(&self, maze: &Maze, writer: W) -> io::Result<gif::Encoder<W>> {
+        let (width, height) = self.frame_size(maze)?;
+        let mut encoder = gif::Encoder::new(writer, width, height, &[]).map_err(to_io_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(to_io_error)?;
+        Ok(encoder)
+    }
+
+    fn frame_size(&self, maze: &Maze) -> io::Result<(u16, u16)> {
+        let (width, height) = PngWriter::new().cell_size(self.cell_size).image_size(maze);
+        gif_size(width, height)
+    }
+
+    fn write_frame<W: Write>(
+        &self,
+        encoder: &mut gif::Encoder<W>,
+        maze: &Maze,
+        color_map: &ColorMap,
+        last: bool,
+    ) -> io::Result<()> {
+        let canvas = PngWriter::new()
+            .cell_size(self.cell_size)
+            .theme(&self.theme)
+            .render(maze, Some(color_map));
+
+        let mut frame = to_frame(&canvas)?;
+        let delay = if last {
+            self.final_delay
+        } else {
+            self.frame_delay
+        };
+        frame.delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        encoder.write_frame(&frame).map_err(to_io_error)
+    }
+}
+
+// Mazes only use a handful of colors, so an exact palette is built whenever possible
+// instead of quantizing every frame.
+fn to_frame(canvas: &Canvas) -> io::Result<gif::Frame<'static>> {
+    let (width, height) = gif_size(canvas.width(), canvas.height())?;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut indices = Vec::with_capacity(canvas.width() * canvas.height());
+
+    for pixel in canvas.pixels().chunks_exact(4) {
+        let rgb = [pixel[0], pixel[1], pixel[2]];
+        let index = match palette.iter().position(|&c| c == rgb) {
+            Some(index) => index,
+            None if palette.len() < 256 => {
+                palette.push(rgb);
+                palette.len() - 1
+            }
+            None => {
+                let mut pixels = canvas.pixels().to_vec();
+                return Ok(gif::Frame::from_rgba_speed(width, height, &mut pixels, 10));
+            }
+        };
+        indices.push(index as u8);
+    }
+
+    Ok(gif::Frame {
+        width,
+        height,
+        buffer: indices.into(),
+        palette: Some(palette.concat()),
+        ..gif::Frame::default()
+    })
+}
+
+// GIF sizes are 16 bits wide.
+fn gif_size(width: usize, height: usize) -> io::Result<(u16, u16)> {
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}x{} pixels is too large for a GIF", width, height),
+        )),
+    }
+}
+
+fn to_io_error(error: gif::EncodingError) -> io::Error {
+    match error {
+        gif::EncodingError::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::Other, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::time::Duration;
+
+    use crate::generation::GeneratorKind;
+    use crate::maze::Maze;
+    use crate::raster::PngWriter;
+    use crate::recording::GifRecorder;
+    use crate::solving::SolverKind;
+
+    // The delay and size of every frame.
+    fn decode(gif: &[u8]) -> Vec<(u16, u16, u16)> {
+        let mut decoder = gif::DecodeOptions::new().read_info(gif).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.width, frame.height));
+        }
+        frames
+    }
+
+    #[test]
+    fn generation_frames() {
+        let steps = {
+            let mut generator = GeneratorKind::Backtracking.build(4, 3, Some(1));
+            let mut steps = 0;
+            while !generator.is_done() {
+                generator.next_step();
+                steps += 1;
+            }
+            steps
+        };
+        let recorder = GifRecorder::new()
+            .cell_size(10.0)
+            .frame_skip(3)
+            .frame_delay(Duration::from_millis(50))
+            .final_delay(Duration::from_secs(1));
+        let mut gif = Vec::new();
+        let mut generator = GeneratorKind::Backtracking.build(4, 3, Some(1));
+        recorder
+            .record_generation(generator.as_mut(), &mut gif)
+            .unwrap();
+
+        let frames = decode(&gif);
+        // Every third step, and the last one.
+        assert_eq!(frames.len(), (steps - 1) / 3 + 1);
+        let (width, height) = PngWriter::new()
+            .cell_size(10.0)
+            .image_size(generator.maze());
+        for (i, &frame) in frames.iter().enumerate() {
+            let delay = if i == frames.len() - 1 { 100 } else { 5 };
+            assert_eq!(frame, (delay, width as u16, height as u16));
+        }
+    }
+
+    #[test]
+    fn solving_frames() {
+        let maze = GeneratorKind::Backtracking.build(5, 5, Some(2)).generate();
+        for kind in SolverKind::ALL {
+            let steps = {
+                let mut solver = kind.build(&maze);
+                let mut steps = 1;
+                while solver.next_step().is_none() && !solver.is_done() {
+                    steps += 1;
+                }
+                steps
+            };
+            let mut gif = Vec::new();
+            GifRecorder::new()
+                .final_delay(Duration::from_millis(500))
+                .record_solving(&maze, kind.build(&maze).as_mut(), &mut gif)
+                .unwrap();
+
+            let frames = decode(&gif);
+            assert_eq!(frames.len(), steps, "{}", kind);
+            assert_eq!(frames.last().unwrap().0, 50);
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_images() {
+        let maze = Maze::new(1, 1);
+        let mut gif = Vec::new();
+        let error = GifRecorder::new()
+            .cell_size(70000.0)
+            .record_solving(&maze, SolverKind::AStar.build(&maze).as_mut(), &mut gif)
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::color::{Color, WALL_COLOR};
+use crate::grid::Grid;
+use crate::layout::{CellLayout, Rectangle};
+use crate::maze::Point;
+use crate::scene::Scene;
+
+pub trait Renderer {
+    // Size of the drawing area, anything outside of it may be skipped.
+    fn view_size(&self) -> [f64; 2];
+
+    fn clear(&mut self, color: Color);
+
+    fn draw_rect(&mut self, color: Color, rect: Rectangle);
+
+    // Line from (x1, y1) to (x2, y2).
+    fn draw_line(&mut self, color: Color, thickness: f64, line: [f64; 4]);
+
+    fn draw_polygon(&mut self, color: Color, corners: &[[f64; 2]]);
+
+    fn draw_ellipse(&mut self, color: Color, rect: Rectangle) {
+        self.draw_rect(color, rect);
+    }
+
+    // `position` is the left end of the baseline. Backends without fonts draw nothing.
+    fn draw_text(&mut self, _color: Color, _font_size: u32, _position: [f64; 2], _text: &str) {}
+}
+
+pub struct MazeDrawer {
+    pub layout: CellLayout,
+    wall_color: Color,
+}
+
+impl Default for MazeDrawer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MazeDrawer {
+    pub fn new() -> Self {
+        Self {
+            layout: CellLayout::default(),
+            wall_color: WALL_COLOR,
+        }
+    }
+
+    pub fn x_offset(mut self, x_offset: f64) -> Self {
+        self.layout.x_offset = x_offset;
+        self
+    }
+
+    pub fn y_offset(mut self, y_offset: f64) -> Self {
+        self.layout.y_offset = y_offset;
+        self
+    }
+
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.set_cell_size(cell_size);
+        self
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: f64) {
+        self.layout.cell_size = cell_size;
+    }
+
+    pub fn grid(mut self, grid: Grid) -> Self {
+        self.layout.grid = grid;
+        self
+    }
+
+    pub fn wall_color(mut self, color: Color) -> Self {
+        self.set_wall_color(color);
+        self
+    }
+
+    pub fn set_wall_color(&mut self, color: Color) {
+        self.wall_color = color;
+    }
+
+    // The scene must be up to date, see `Scene::update`.
+    pub fn draw_maze<R: Renderer + ?Sized>(&self, renderer: &mut R, scene: &Scene) {
+        if !scene.grid().is_square() {
+            self.draw_shapes(renderer, scene);
+            return;
+        }
+        let [view_width, view_height] = renderer.view_size();
+        let (columns, rows) =
+            self.layout
+                .visible_cells(view_width, view_height, scene.width(), scene.height());
+
+        for (color, rect) in scene.fills(rows.clone()) {
+            renderer.draw_rect(*color, self.to_gui_rectangle(rect));
+        }
+        for rect in scene.walls(columns, rows) {
+            renderer.draw_rect(self.wall_color, self.to_gui_rectangle(rect));
+        }
+    }
+
+    // Only the cells and walls in sight are drawn, as with `visible_cells` on square grids.
+    fn draw_shapes<R: Renderer + ?Sized>(&self, renderer: &mut R, scene: &Scene) {
+        let [view_width, view_height] = renderer.view_size();
+        let bounds = self.layout.visible_bounds(view_width, view_height);
+        // As thick as the walls of square grids.
+        let thickness = 2.0 * self.layout.wall_thickness();
+        // Walls along the edges of the window are drawn whole.
+        let margin = thickness / self.layout.cell_size;
+
+        for (color, corners) in scene.shapes() {
+            if !overlaps(&bounds, margin, corners) {
+                continue;
+            }
+            let corners: Vec<[f64; 2]> = corners
+                .iter()
+                .map(|corner| self.layout.to_gui_point(*corner))
+                .collect();
+            renderer.draw_polygon(*color, &corners);
+        }
+        for [x1, y1, x2, y2] in scene.sides() {
+            if !overlaps(&bounds, margin, &[[*x1, *y1], [*x2, *y2]]) {
+                continue;
+            }
+            let [x1, y1] = self.layout.to_gui_point([*x1, *y1]);
+            let [x2, y2] = self.layout.to_gui_point([*x2, *y2]);
+            renderer.draw_line(self.wall_color, thickness, [x1, y1, x2, y2]);
+        }
+    }
+
+    // On a cell of a `width` x `height` maze.
+    pub fn draw_token<R: Renderer + ?Sized>(
+        &self,
+        renderer: &mut R,
+        point: &Point,
+        width: usize,
+        height: usize,
+        color: Color,
+    ) {
+        let [x, y, size, _] = self.layout.cell_rectangle(point, width, height);
+        let margin = size / 4.0;
+        renderer.draw_ellipse(
+            color,
+            [
+                x + margin,
+                y + margin,
+                size - 2.0 * margin,
+                size - 2.0 * margin,
+            ],
+        );
+    }
+
+    // The scene is in grid coordinates.
+    fn to_gui_rectangle(&self, [x, y, w, h]: &Rectangle) -> Rectangle {
+        let CellLayout {
+            x_offset,
+            y_offset,
+            cell_size,
+            ..
+        } = self.layout;
+        [
+            x_offset + x * cell_size,
+            y_offset + y * cell_size,
+            w * cell_size,
+            h * cell_size,
+        ]
+    }
+}
+
+// Whether the bounding box of `points`, grown by `margin`, overlaps `bounds`.
+fn overlaps([x, y, width, height]: &Rectangle, margin: f64, points: &[[f64; 2]]) -> bool {
+    let (mut left, mut top) = (f64::INFINITY, f64::INFINITY);
+    let (mut right, mut bottom) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for [px, py] in points {
+        left = left.min(*px);
+        top = top.min(*py);
+        right = right.max(*px);
+        bottom = bottom.max(*py);
+    }
+    right + margin >= *x
+        && left - margin <= x + width
+        && bottom + margin >= *y
+        && top - margin <= y + height
+}
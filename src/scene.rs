@@ -0,0 +1,335 @@
+use std::ops::Range;
+
+use crate::color::Color;
+use crate::grid::Grid;
+use crate::layout::Rectangle;
+use crate::maze::{Maze, Point};
+
+// Half the thickness of a wall shared by two cells, in cells.
+const WALL_THICKNESS: f64 = 1.0 / 15.0;
+
+// Geometry of a maze in grid coordinates (one unit per cell), built once and updated row by row
+// as cells change. Neighbouring cells of the same color are merged into a single rectangle and
+// walls are drawn once per edge rather than once per cell.
+//
+// Other grids than the square one are kept as the polygons of the colored cells and the
+// sides with a wall, row by row.
+pub struct Scene {
+    width: usize,
+    height: usize,
+    grid: Grid,
+    // Row-major, with rows as wide as the widest one.
+    columns: usize,
+    colors: Vec<Option<Color>>,
+    fills: Vec<Vec<(Color, Rectangle)>>,
+    horizontal_walls: Vec<Vec<Rectangle>>,
+    vertical_walls: Vec<Vec<Rectangle>>,
+    dirty_rows: Vec<bool>,
+    dirty_horizontal_walls: Vec<bool>,
+    dirty_vertical_walls: Vec<bool>,
+    shapes: Vec<Vec<(Color, Vec<[f64; 2]>)>>,
+    sides: Vec<Vec<[f64; 4]>>,
+    dirty_sides: Vec<bool>,
+}
+
+impl Scene {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_grid(Grid::Square, width, height)
+    }
+
+    pub fn with_grid(grid: Grid, width: usize, height: usize) -> Self {
+        let columns = grid.row_width(height - 1, width);
+        Self {
+            width,
+            height,
+            grid,
+            columns,
+            colors: vec![None; columns * height],
+            fills: vec![Vec::new(); height],
+            horizontal_walls: vec![Vec::new(); height + 1],
+            vertical_walls: vec![Vec::new(); width + 1],
+            dirty_rows: vec![true; height],
+            dirty_horizontal_walls: vec![true; height + 1],
+            dirty_vertical_walls: vec![true; width + 1],
+            shapes: vec![Vec::new(); height],
+            sides: vec![Vec::new(); height],
+            dirty_sides: vec![true; height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    pub fn color(&self, point: &Point) -> Option<Color> {
+        self.colors[point.y * self.columns + point.x]
+    }
+
+    // Returns the previous color.
+    pub fn set_color(&mut self, point: &Point, color: Option<Color>) -> Option<Color> {
+        let index = point.y * self.columns + point.x;
+        let previous = std::mem::replace(&mut self.colors[index], color);
+        if previous != color {
+            self.dirty_rows[point.y] = true;
+        }
+        previous
+    }
+
+    pub fn map_colors<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Point, Option<Color>) -> Option<Color>,
+    {
+        for y in 0..self.height {
+            for x in 0..self.grid.row_width(y, self.width) {
+                let point = Point { x, y };
+                let color = f(point, self.color(&point));
+                self.set_color(&point, color);
+            }
+        }
+    }
+
+    pub fn wall_changed(&mut self, p1: &Point, p2: &Point) {
+        self.dirty_sides[p1.y] = true;
+        self.dirty_sides[p2.y] = true;
+        if !self.grid.is_square() {
+            return;
+        }
+        if p1.x == p2.x {
+            self.dirty_horizontal_walls[p1.y.max(p2.y)] = true;
+        } else {
+            self.dirty_vertical_walls[p1.x.max(p2.x)] = true;
+        }
+    }
+
+    pub fn walls_changed(&mut self) {
+        self.dirty_horizontal_walls
+            .iter_mut()
+            .for_each(|d| *d = true);
+        self.dirty_vertical_walls.iter_mut().for_each(|d| *d = true);
+        self.dirty_sides.iter_mut().for_each(|d| *d = true);
+    }
+
+    // Rebuilds the geometry of the rows and walls that changed since the last update. The
+    // maze must be on the grid of the scene.
+    pub fn update(&mut self, maze: &Maze) {
+        if !self.grid.is_square() {
+            self.update_shapes(maze);
+            return;
+        }
+
+        for y in 0..self.height {
+            if std::mem::take(&mut self.dirty_rows[y]) {
+                self.fills[y] = self.row_fills(y);
+            }
+        }
+
+        let (width, height) = (self.width as f64, self.height as f64);
+        for y in 0..=self.height {
+            if std::mem::take(&mut self.dirty_horizontal_walls[y]) {
+                self.horizontal_walls[y] = horizontal_wall_runs(maze, y)
+                    .into_iter()
+                    .map(|run| {
+                        let x = run.start as f64 - WALL_THICKNESS;
+                        let length = run.len() as f64 + 2.0 * WALL_THICKNESS;
+                        clip(
+                            [x, y as f64 - WALL_THICKNESS, length, 2.0 * WALL_THICKNESS],
+                            width,
+                            height,
+                        )
+                    })
+                    .collect();
+            }
+        }
+        for x in 0..=self.width {
+            if std::mem::take(&mut self.dirty_vertical_walls[x]) {
+                self.vertical_walls[x] = vertical_wall_runs(maze, x)
+                    .into_iter()
+                    .map(|run| {
+                        let y = run.start as f64 - WALL_THICKNESS;
+                        let length = run.len() as f64 + 2.0 * WALL_THICKNESS;
+                        clip(
+                            [x as f64 - WALL_THICKNESS, y, 2.0 * WALL_THICKNESS, length],
+                            width,
+                            height,
+                        )
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    fn update_shapes(&mut self, maze: &Maze) {
+        for y in 0..self.height {
+            if std::mem::take(&mut self.dirty_rows[y]) {
+                let (width, height) = (self.width, self.height);
+                self.shapes[y] = (0..self.grid.row_width(y, width))
+                    .map(|x| Point { x, y })
+                    .filter_map(|point| Some((point, self.color(&point)?)))
+                    .flat_map(|(point, color)| {
+                        let tiles = self.grid.tiles(&point, width, height);
+                        tiles.into_iter().map(move |tile| (color, tile))
+                    })
+                    .collect();
+            }
+            if std::mem::take(&mut self.dirty_sides[y]) {
+                self.sides[y] = wall_sides(maze, y);
+            }
+        }
+    }
+
+    // Polygons of the colored cells, other grids than the square one only.
+    pub fn shapes(&self) -> impl Iterator<Item = &(Color, Vec<[f64; 2]>)> {
+        self.shapes.iter().flatten()
+    }
+
+    // Walls, other grids than the square one only.
+    pub fn sides(&self) -> impl Iterator<Item = &[f64; 4]> {
+        self.sides.iter().flatten()
+    }
+
+    // Filled rectangles of the given rows, to draw before the walls.
+    pub fn fills(&self, rows: Range<usize>) -> impl Iterator<Item = &(Color, Rectangle)> {
+        self.fills[rows].iter().flatten()
+    }
+
+    // Walls bordering the given columns and rows.
+    pub fn walls(
+        &self,
+        columns: Range<usize>,
+        rows: Range<usize>,
+    ) -> impl Iterator<Item = &Rectangle> {
+        self.horizontal_walls[rows.start..=rows.end]
+            .iter()
+            .chain(self.vertical_walls[columns.start..=columns.end].iter())
+            .flatten()
+    }
+
+    fn row_fills(&self, y: usize) -> Vec<(Color, Rectangle)> {
+        let row = &self.colors[y * self.columns..y * self.columns + self.width];
+        let mut fills = Vec::new();
+        let mut x = 0;
+        while x < self.width {
+            let start = x;
+            while x < self.width && row[x] == row[start] {
+                x += 1;
+            }
+            if let Some(color) = row[start] {
+                fills.push((color, [start as f64, y as f64, (x - start) as f64, 1.0]));
+            }
+        }
+        fills
+    }
+}
+
+fn clip(rectangle: Rectangle, width: f64, height: f64) -> Rectangle {
+    let [x, y, w, h] = rectangle;
+    let (x1, y1) = (x.max(0.0), y.max(0.0));
+    let (x2, y2) = ((x + w).min(width), (y + h).min(height));
+    [x1, y1, x2 - x1, y2 - y1]
+}
+
+// Columns with a wall on the north side of row `y` (0..=height), merged into runs.
+pub fn horizontal_wall_runs(maze: &Maze, y: usize) -> Vec<Range<usize>> {
+    runs(maze.width, |x| maze.horizontal_wall(x, y))
+}
+
+// Rows with a wall on the west side of column `x` (0..=width), merged into runs.
+pub fn vertical_wall_runs(maze: &Maze, x: usize) -> Vec<Range<usize>> {
+    runs(maze.height, |y| maze.vertical_wall(x, y))
+}
+
+// Walls of the cells of row `y` in grid coordinates. A wall between two cells belongs to
+// the first of them in row-major order, so that it is only listed once.
+pub fn wall_sides(maze: &Maze, y: usize) -> Vec<[f64; 4]> {
+    let grid = maze.grid();
+    let mut sides = Vec::new();
+    for x in 0..maze.row_width(y) {
+        let point = Point { x, y };
+        let walls = maze.cell(&point).walls();
+        for direction in maze.directions(&point).iter().filter(|d| walls.contains(d)) {
+            let owned = match maze.neighbor(&point, direction) {
+                Some(neighbor) => (neighbor.y, neighbor.x) > (y, x),
+                None => true,
+            };
+            if owned {
+                sides.extend(grid.side_segments(&point, direction, maze.width, maze.height));
+            }
+        }
+    }
+    sides
+}
+
+fn runs<F: Fn(usize) -> bool>(length: usize, present: F) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    for i in 0..=length {
+        match (i < length && present(i), run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                runs.push(start..i);
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::Grid;
+    use crate::maze::{Maze, Point};
+    use crate::scene::Scene;
+
+    #[test]
+    fn merges_fills_and_updates_dirty_rows() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let mut maze = Maze::new(3, 2);
+        let mut scene = Scene::new(3, 2);
+        scene.set_color(&Point { x: 0, y: 0 }, Some(red));
+        scene.set_color(&Point { x: 1, y: 0 }, Some(red));
+        scene.update(&maze);
+
+        assert_eq!(
+            scene.fills(0..2).collect::<Vec<_>>(),
+            vec![&(red, [0.0, 0.0, 2.0, 1.0])]
+        );
+        // Three rows of horizontal walls and four columns of vertical walls.
+        assert_eq!(scene.walls(0..3, 0..2).count(), 7);
+
+        maze.remove_wall_between(&Point { x: 1, y: 0 }, &Point { x: 1, y: 1 });
+        scene.wall_changed(&Point { x: 1, y: 0 }, &Point { x: 1, y: 1 });
+        scene.set_color(&Point { x: 2, y: 1 }, Some(red));
+        scene.update(&maze);
+
+        assert_eq!(scene.walls(0..3, 0..2).count(), 8);
+        assert_eq!(scene.fills(1..2).count(), 1);
+    }
+
+    #[test]
+    fn hex_shapes_and_sides() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let mut maze = Maze::with_grid(Grid::Hex, 2, 1);
+        let mut scene = Scene::with_grid(Grid::Hex, 2, 1);
+        scene.set_color(&Point { x: 1, y: 0 }, Some(red));
+        scene.update(&maze);
+
+        assert_eq!(scene.grid(), Grid::Hex);
+        assert_eq!(scene.shapes().count(), 1);
+        // The shared side is only listed once.
+        assert_eq!(scene.sides().count(), 11);
+
+        maze.remove_wall_between(&Point { x: 0, y: 0 }, &Point { x: 1, y: 0 });
+        scene.wall_changed(&Point { x: 0, y: 0 }, &Point { x: 1, y: 0 });
+        scene.update(&maze);
+        assert_eq!(scene.sides().count(), 10);
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::code::{self, CodeError};
+use crate::color::{self, Color};
+use crate::generation::GeneratorKind;
+use crate::maze::{Maze, Point};
+
+// Everything needed to bring a maze back on screen: the maze itself (start and end
+// included), how it was generated and the colors painted over its cells.
+pub struct Session {
+    pub maze: Maze,
+    pub generator: GeneratorKind,
+    pub seed: Option<u64>,
+    pub colors: Vec<(Point, Color)>,
+}
+
+impl Session {
+    // Session files are `key = value` lines, `maze` being a maze code and every `color`
+    // line a cell followed by its color, e.g. `color = 3,4 #0000ff`.
+    pub fn parse(content: &str) -> Result<Self, SessionError> {
+        let mut maze = None;
+        let mut generator = GeneratorKind::Backtracking;
+        let mut seed = None;
+        let mut colors = Vec::new();
+
+        for (number, line) in content.lines().enumerate() {
+            let line_number = number + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(SessionError::Syntax(line_number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || SessionError::InvalidValue(line_number, key.to_string());
+
+            match key {
+                "maze" => maze = Some(code::from_code(value).map_err(SessionError::Code)?),
+                "generator" => generator = value.parse().map_err(|_| invalid())?,
+                "seed" => seed = Some(value.parse().map_err(|_| invalid())?),
+                "color" => colors.push(parse_color(value).ok_or_else(invalid)?),
+                _ => return Err(SessionError::UnknownKey(line_number, key.to_string())),
+            }
+        }
+
+        let maze = maze.ok_or(SessionError::MissingMaze)?;
+        if let Some((point, _)) = colors.iter().find(|(point, _)| !maze.contains(point)) {
+            return Err(SessionError::OutOfBounds(*point));
+        }
+        Ok(Self {
+            maze,
+            generator,
+            seed,
+            colors,
+        })
+    }
+
+    pub fn load<P: AsRef<std::path::Path>>(file: P) -> Result<Self, SessionError> {
+        let content = fs::read_to_string(file).map_err(SessionError::Io)?;
+        Session::parse(&content)
+    }
+
+    pub fn save<P: AsRef<std::path::Path>>(&self, file: P) -> io::Result<()> {
+        fs::write(file, self.to_string())
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# maze session")?;
+        writeln!(f, "generator = {}", self.generator)?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed = {}", seed)?;
+        }
+        writeln!(f, "maze = {}", code::to_code(&self.maze))?;
+        for (point, color) in self.colors.iter() {
+            writeln!(f, "color = {},{} {}", point.x, point.y, to_hex_rgba(*color))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_color(value: &str) -> Option<(Point, Color)> {
+    let (point, color) = value.split_once(' ')?;
+    let (x, y) = point.split_once(',')?;
+    let point = Point {
+        x: x.trim().parse().ok()?,
+        y: y.trim().parse().ok()?,
+    };
+    Some((point, color::from_hex(color.trim())?))
+}
+
+fn to_hex_rgba(color: Color) -> String {
+    let alpha = (color[3].clamp(0.0, 1.0) * 255.0).round() as u8;
+    if alpha == 255 {
+        color::to_hex(color)
+    } else {
+        format!("{}{:02x}", color::to_hex(color), alpha)
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    Syntax(usize),
+    UnknownKey(usize, String),
+    InvalidValue(usize, String),
+    Code(CodeError),
+    MissingMaze,
+    OutOfBounds(Point),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Io(error) => write!(f, "{}", error),
+            SessionError::Syntax(line) => write!(f, "line {}: expected `key = value`", line),
+            SessionError::UnknownKey(line, key) => {
+                write!(f, "line {}: unknown key '{}'", line, key)
+            }
+            SessionError::InvalidValue(line, key) => {
+                write!(f, "line {}: invalid value for '{}'", line, key)
+            }
+            SessionError::Code(error) => write!(f, "{}", error),
+            SessionError::MissingMaze => write!(f, "the session has no maze"),
+            SessionError::OutOfBounds(point) => {
+                write!(f, "point ({}, {}) is outside of the maze", point.x, point.y)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::generation::GeneratorKind;
+    use crate::maze::Point;
+    use crate::session::{Session, SessionError};
+
+    #[test]
+    fn round_trip() {
+        let mut maze = GeneratorKind::Prim.build(5, 4, Some(9)).generate();
+        maze.set_start(1, 2);
+        let session = Session {
+            maze,
+            generator: GeneratorKind::Prim,
+            seed: Some(9),
+            colors: vec![
+                (Point { x: 1, y: 2 }, [0.0, 1.0, 0.0, 1.0]),
+                (Point { x: 4, y: 3 }, [1.0, 0.0, 0.0, 0.6]),
+            ],
+        };
+
+        let loaded = Session::parse(&session.to_string()).unwrap();
+        assert_eq!(loaded.to_string(), session.to_string());
+        assert_eq!(loaded.maze.start(), Point { x: 1, y: 2 });
+        assert_eq!(loaded.generator, GeneratorKind::Prim);
+        assert_eq!(loaded.seed, Some(9));
+        assert_eq!(loaded.colors[0], session.colors[0]);
+    }
+
+    #[test]
+    fn invalid_sessions() {
+        assert!(matches!(
+            Session::parse("seed = 4"),
+            Err(SessionError::MissingMaze)
+        ));
+        assert!(matches!(
+            Session::parse("# comment\nseed = four"),
+            Err(SessionError::InvalidValue(2, _))
+        ));
+        assert!(matches!(
+            Session::parse("zoom = 2"),
+            Err(SessionError::UnknownKey(1, _))
+        ));
+    }
+}
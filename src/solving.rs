@@ -1,11 +1,89 @@
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
 
-use crate::maze::{Maze, Point};
+use crate::maze::{Direction, Maze, Point};
 
 use std::cmp::Ordering;
 
 pub type Path = Vec<Point>;
 
+pub trait Solver {
+    fn name(&self) -> &'static str;
+
+    // Returns the path once the end has been reached.
+    fn next_step(&mut self) -> Option<Path>;
+
+    fn is_done(&self) -> bool;
+
+    fn nodes_expanded(&self) -> usize;
+
+    // Cell expanded by the last step, if any.
+    fn last_expanded(&self) -> Option<Point>;
+
+    fn solve(&mut self) -> Option<Path> {
+        while !self.is_done() {
+            if let Some(path) = self.next_step() {
+                return Some(path);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SolverKind {
+    AStar,
+    Bfs,
+    Dfs,
+    WallFollower,
+}
+
+impl SolverKind {
+    pub const ALL: [SolverKind; 4] = [
+        SolverKind::AStar,
+        SolverKind::Bfs,
+        SolverKind::Dfs,
+        SolverKind::WallFollower,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolverKind::AStar => "astar",
+            SolverKind::Bfs => "bfs",
+            SolverKind::Dfs => "dfs",
+            SolverKind::WallFollower => "wall-follower",
+        }
+    }
+
+    pub fn build<'a>(&self, maze: &'a Maze) -> Box<dyn Solver + 'a> {
+        match self {
+            SolverKind::AStar => Box::new(AStarSolver::new(maze)),
+            SolverKind::Bfs => Box::new(GraphSearchSolver::breadth_first(maze)),
+            SolverKind::Dfs => Box::new(GraphSearchSolver::depth_first(maze)),
+            SolverKind::WallFollower => Box::new(WallFollowerSolver::new(maze)),
+        }
+    }
+}
+
+impl fmt::Display for SolverKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SolverKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SolverKind::ALL
+            .iter()
+            .find(|kind| kind.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown solver '{}'", s))
+    }
+}
+
 #[derive(Eq, Debug)]
 struct CostState {
     cost: usize,
@@ -66,11 +144,13 @@ pub struct AStarSolver<'a> {
     came_from: HashMap<Point, Point>,
     g_score: HashMap<Point, usize>,
     state: SolverState,
+    nodes_expanded: usize,
+    last_expanded: Option<Point>,
 }
 
 impl<'a> AStarSolver<'a> {
     fn heuristic(&self, node: Point) -> usize {
-        node.distance(&self.maze.end())
+        self.maze.distance(&node, &self.maze.end())
     }
 
     pub fn new(maze: &'a Maze) -> Self {
@@ -85,6 +165,8 @@ impl<'a> AStarSolver<'a> {
             g_score,
             in_open_set,
             state: SolverState::Clear,
+            nodes_expanded: 0,
+            last_expanded: None,
         }
     }
 
@@ -98,15 +180,46 @@ impl<'a> AStarSolver<'a> {
             .push(CostState::new(self.heuristic(start), start));
         self.in_open_set.insert(start);
 
-        for x in 0..self.maze.width {
-            for y in 0..self.maze.height {
-                self.g_score.insert(Point { x, y }, std::usize::MAX);
-            }
+        for point in self.maze.points() {
+            self.g_score.insert(point, usize::MAX);
         }
         self.g_score.insert(start, 0);
         self.state = SolverState::Initialised;
     }
 
+    pub fn current_cost_map(&self) -> &HashMap<Point, usize> {
+        &self.g_score
+    }
+
+    pub fn frontier(&self) -> &HashSet<Point> {
+        &self.in_open_set
+    }
+}
+
+fn reconstruct_path(came_from: &HashMap<Point, Point>, node: &Point) -> Path {
+    let mut path = vec![*node];
+    let mut current = *node;
+    while let Some(&prev) = came_from.get(&current) {
+        current = prev;
+        path.push(current);
+    }
+    path.reverse();
+    path
+}
+
+// Neighbors of `point` that are not separated from it by a wall.
+fn open_neighbors(maze: &Maze, point: &Point) -> Vec<Point> {
+    maze.neighbors(point)
+        .into_iter()
+        .filter(|neighbor| !maze.is_wall_present(point, neighbor))
+        .collect()
+}
+
+impl<'a> Solver for AStarSolver<'a> {
+    fn name(&self) -> &'static str {
+        SolverKind::AStar.name()
+    }
+
     fn next_step(&mut self) -> Option<Path> {
         if self.state == SolverState::Clear {
             self.initialize()
@@ -121,15 +234,16 @@ impl<'a> AStarSolver<'a> {
 
         let current = self.open_set.pop().unwrap();
         self.in_open_set.remove(&current.position);
+        self.nodes_expanded += 1;
+        self.last_expanded = Some(current.position);
 
         if current.position == self.maze.end() {
-            return Some(self.reconstruct_path(&current.position));
+            return Some(reconstruct_path(&self.came_from, &current.position));
         }
 
         let maze = self.maze;
-        for &neighbor in current
-            .position
-            .neighbors(self.maze.width, self.maze.height)
+        for &neighbor in maze
+            .neighbors(&current.position)
             .iter()
             .filter(|&p| !maze.is_wall_present(&current.position, p))
         {
@@ -151,35 +265,220 @@ impl<'a> AStarSolver<'a> {
         None
     }
 
-    pub fn solve(&mut self) -> Option<Path> {
-        while self.state != SolverState::Done {
-            if let Some(path) = self.next_step() {
-                return Some(path);
+    fn is_done(&self) -> bool {
+        self.state == SolverState::Done
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn last_expanded(&self) -> Option<Point> {
+        self.last_expanded
+    }
+}
+
+// Breadth-first and depth-first search only differ by the end of the frontier they expand.
+pub struct GraphSearchSolver<'a> {
+    maze: &'a Maze,
+    frontier: VecDeque<Point>,
+    seen: HashSet<Point>,
+    came_from: HashMap<Point, Point>,
+    depth_first: bool,
+    done: bool,
+    nodes_expanded: usize,
+    last_expanded: Option<Point>,
+}
+
+impl<'a> GraphSearchSolver<'a> {
+    pub fn breadth_first(maze: &'a Maze) -> Self {
+        Self::new(maze, false)
+    }
+
+    pub fn depth_first(maze: &'a Maze) -> Self {
+        Self::new(maze, true)
+    }
+
+    fn new(maze: &'a Maze, depth_first: bool) -> Self {
+        let start = maze.start();
+        Self {
+            maze,
+            frontier: vec![start].into(),
+            seen: vec![start].into_iter().collect(),
+            came_from: HashMap::new(),
+            depth_first,
+            done: false,
+            nodes_expanded: 0,
+            last_expanded: None,
+        }
+    }
+}
+
+impl<'a> Solver for GraphSearchSolver<'a> {
+    fn name(&self) -> &'static str {
+        if self.depth_first {
+            SolverKind::Dfs.name()
+        } else {
+            SolverKind::Bfs.name()
+        }
+    }
+
+    fn next_step(&mut self) -> Option<Path> {
+        let current = if self.depth_first {
+            self.frontier.pop_back()
+        } else {
+            self.frontier.pop_front()
+        };
+        let current = match current {
+            Some(current) if !self.done => current,
+            _ => {
+                self.done = true;
+                return None;
+            }
+        };
+        self.nodes_expanded += 1;
+        self.last_expanded = Some(current);
+
+        if current == self.maze.end() {
+            self.done = true;
+            return Some(reconstruct_path(&self.came_from, &current));
+        }
+
+        for neighbor in open_neighbors(self.maze, &current) {
+            if self.seen.insert(neighbor) {
+                self.came_from.insert(neighbor, current);
+                self.frontier.push_back(neighbor);
             }
         }
         None
     }
 
-    pub fn current_cost_map(&self) -> &HashMap<Point, usize> {
-        &self.g_score
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn last_expanded(&self) -> Option<Point> {
+        self.last_expanded
+    }
+}
+
+// Keeps its right hand on the wall. Only guaranteed to reach the end when both the start
+// and the end are connected to the outer wall, e.g. in mazes without loops.
+pub struct WallFollowerSolver<'a> {
+    maze: &'a Maze,
+    position: Point,
+    facing: Direction,
+    // The first move out of the start, coming back to it means the walk loops forever.
+    first_move: Option<Direction>,
+    path: Path,
+    done: bool,
+    nodes_expanded: usize,
+    last_expanded: Option<Point>,
+}
+
+impl<'a> WallFollowerSolver<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        Self {
+            maze,
+            position: maze.start(),
+            facing: Direction::East,
+            first_move: None,
+            path: vec![maze.start()],
+            done: false,
+            nodes_expanded: 0,
+            last_expanded: None,
+        }
+    }
+
+    // From the sharpest turn to the right to going back: the sides counter-clockwise from
+    // the one the cell was entered through. Polar cells have no side behind the initial
+    // facing, their first side is used instead.
+    fn next_move(&self) -> Option<(Direction, Point)> {
+        let directions = self.maze.directions(&self.position);
+        let count = directions.len();
+        let back = directions
+            .iter()
+            .position(|direction| *direction == self.facing.opposite())
+            .unwrap_or(0);
+        (0..count)
+            .map(|turn| &directions[(back + 2 * count - 1 - turn) % count])
+            .find_map(|direction| {
+                self.maze
+                    .neighbor(&self.position, direction)
+                    .filter(|next| !self.maze.is_wall_present(&self.position, next))
+                    .map(|next| (direction.clone(), next))
+            })
+    }
+}
+
+impl<'a> Solver for WallFollowerSolver<'a> {
+    fn name(&self) -> &'static str {
+        SolverKind::WallFollower.name()
     }
 
-    fn reconstruct_path(&self, node: &Point) -> Path {
-        let mut path = vec![*node];
-        let mut current = *node;
-        while let Some(&prev) = self.came_from.get(&current) {
-            current = prev;
-            path.push(current);
+    fn next_step(&mut self) -> Option<Path> {
+        if self.done {
+            return None;
+        }
+        self.nodes_expanded += 1;
+        self.last_expanded = Some(self.position);
+
+        if self.position == self.maze.end() {
+            self.done = true;
+            return Some(self.path.clone());
+        }
+
+        let (direction, next) = match self.next_move() {
+            Some(next_move) => next_move,
+            None => {
+                self.done = true;
+                return None;
+            }
+        };
+        if self.position == self.maze.start() {
+            match &self.first_move {
+                Some(first_move) if *first_move == direction => {
+                    self.done = true;
+                    return None;
+                }
+                Some(_) => {}
+                None => self.first_move = Some(direction.clone()),
+            }
+        }
+
+        // Dead ends are walked back out of, which erases them from the path.
+        match self.path.iter().position(|point| *point == next) {
+            Some(index) => self.path.truncate(index + 1),
+            None => self.path.push(next),
         }
-        path.reverse();
-        path
+        self.position = next;
+        self.facing = direction;
+        None
+    }
+
+    fn is_done(&self) -> bool {
+        self.done
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn last_expanded(&self) -> Option<Point> {
+        self.last_expanded
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::maze::Point;
-    use crate::solving::CostState;
+    use crate::generation::GeneratorKind;
+    use crate::grid::Grid;
+    use crate::maze::{Maze, Point};
+    use crate::solving::{CostState, SolverKind};
     use std::collections::BinaryHeap;
 
     #[test]
@@ -200,4 +499,46 @@ mod tests {
         assert_eq!(heap.pop(), Some(CostState::new(10, point)));
         assert_eq!(heap.pop(), Some(CostState::new(25, point)));
     }
+
+    #[test]
+    fn every_solver_finds_the_unique_path() {
+        for grid in Grid::ALL.iter() {
+            let maze = GeneratorKind::Backtracking
+                .build_with_grid(*grid, 8, 6, Some(4))
+                .generate();
+            let expected = SolverKind::AStar.build(&maze).solve().unwrap();
+
+            for kind in SolverKind::ALL.iter() {
+                let mut solver = kind.build(&maze);
+                assert_eq!(
+                    solver.solve().as_ref(),
+                    Some(&expected),
+                    "{} {}",
+                    kind,
+                    grid
+                );
+                assert!(solver.nodes_expanded() >= expected.len());
+            }
+        }
+    }
+
+    #[test]
+    fn wall_follower_gives_up_on_islands() {
+        // The end is in the middle of an open room, away from the walls.
+        let mut maze = Maze::new(3, 3);
+        for x in 0..3 {
+            for y in 0..3 {
+                let point = Point { x, y };
+                for neighbor in point.neighbors(3, 3) {
+                    maze.remove_wall_between(&point, &neighbor);
+                }
+            }
+        }
+        maze.set_end(1, 1);
+
+        assert!(SolverKind::Bfs.build(&maze).solve().is_some());
+        let mut solver = SolverKind::WallFollower.build(&maze);
+        assert_eq!(solver.solve(), None);
+        assert!(solver.is_done());
+    }
 }
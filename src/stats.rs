@@ -0,0 +1,67 @@
+use std::fmt;
+
+use crate::maze::Maze;
+use crate::solving::SolverKind;
+
+pub struct MazeStats {
+    pub width: usize,
+    pub height: usize,
+    pub dead_ends: usize,
+    pub corridors: usize,
+    pub junctions: usize,
+    pub crossroads: usize,
+    pub solution_length: Option<usize>,
+    pub nodes_expanded: usize,
+}
+
+impl MazeStats {
+    pub fn compute(maze: &Maze, solver: SolverKind) -> Self {
+        let mut stats = Self {
+            width: maze.width,
+            height: maze.height,
+            dead_ends: 0,
+            corridors: 0,
+            junctions: 0,
+            crossroads: 0,
+            solution_length: None,
+            nodes_expanded: 0,
+        };
+
+        for point in maze.points() {
+            let openings = maze
+                .neighbors(&point)
+                .iter()
+                .filter(|n| !maze.is_wall_present(&point, n))
+                .count();
+            match openings {
+                1 => stats.dead_ends += 1,
+                2 => stats.corridors += 1,
+                3 => stats.junctions += 1,
+                0 => {}
+                // Hex cells have up to six openings.
+                _ => stats.crossroads += 1,
+            }
+        }
+
+        let mut solver = solver.build(maze);
+        // The path includes both the start and the end.
+        stats.solution_length = solver.solve().map(|path| path.len() - 1);
+        stats.nodes_expanded = solver.nodes_expanded();
+        stats
+    }
+}
+
+impl fmt::Display for MazeStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "size:            {}x{}", self.width, self.height)?;
+        writeln!(f, "dead ends:       {}", self.dead_ends)?;
+        writeln!(f, "corridors:       {}", self.corridors)?;
+        writeln!(f, "junctions:       {}", self.junctions)?;
+        writeln!(f, "crossroads:      {}", self.crossroads)?;
+        match self.solution_length {
+            Some(length) => writeln!(f, "solution length: {}", length)?,
+            None => writeln!(f, "solution length: unsolvable")?,
+        }
+        writeln!(f, "nodes expanded:  {}", self.nodes_expanded)
+    }
+}
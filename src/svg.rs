@@ -0,0 +1,266 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+use crate::color::{self, Color, BACK_COLOR, END_COLOR, PATH_COLOR, START_COLOR, WALL_COLOR};
+use crate::maze::Maze;
+use crate::scene::{horizontal_wall_runs, vertical_wall_runs, wall_sides};
+use crate::solving::Path;
+use crate::theme::Theme;
+
+pub struct SvgWriter {
+    cell_size: f64,
+    wall_thickness: Option<f64>,
+    back_color: Color,
+    wall_color: Color,
+    path_color: Color,
+    start_color: Color,
+    end_color: Color,
+    markers: bool,
+}
+
+impl Default for SvgWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SvgWriter {
+    pub fn new() -> Self {
+        Self {
+            cell_size: 20.0,
+            wall_thickness: None,
+            back_color: BACK_COLOR,
+            wall_color: WALL_COLOR,
+            path_color: PATH_COLOR,
+            start_color: START_COLOR,
+            end_color: END_COLOR,
+            markers: true,
+        }
+    }
+
+    pub fn cell_size(mut self, cell_size: f64) -> Self {
+        self.cell_size = cell_size;
+        self
+    }
+
+    // Defaults to the same ratio as `MazeDrawer`: a fifteenth of the cell size.
+    pub fn wall_thickness(mut self, wall_thickness: f64) -> Self {
+        self.wall_thickness = Some(wall_thickness);
+        self
+    }
+
+    // Sets every color at once, the setters below override single colors.
+    pub fn theme(mut self, theme: &Theme) -> Self {
+        self.back_color = theme.back;
+        self.wall_color = theme.wall;
+        self.path_color = theme.path;
+        self.start_color = theme.start;
+        self.end_color = theme.end;
+        self
+    }
+
+    pub fn back_color(mut self, color: Color) -> Self {
+        self.back_color = color;
+        self
+    }
+
+    pub fn wall_color(mut self, color: Color) -> Self {
+        self.wall_color = color;
+        self
+    }
+
+    pub fn path_color(mut self, color: Color) -> Self {
+        self.path_color = color;
+        self
+    }
+
+    pub fn start_color(mut self, color: Color) -> Self {
+        self.start_color = color;
+        self
+    }
+
+    pub fn end_color(mut self, color: Color) -> Self {
+        self.end_color = color;
+        self
+    }
+
+    pub fn markers(mut self, markers: bool) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    fn thickness(&self) -> f64 {
+        self.wall_thickness.unwrap_or(self.cell_size / 15.0)
+    }
+
+    // From grid coordinates, see `Grid`.
+    fn to_svg_point(&self, [x, y]: [f64; 2]) -> (f64, f64) {
+        let margin = self.thickness();
+        (margin + x * self.cell_size, margin + y * self.cell_size)
+    }
+
+    pub fn render(&self, maze: &Maze, solution: Option<&Path>) -> String {
+        let grid = maze.grid();
+        let cell_size = self.cell_size;
+        let thickness = self.thickness();
+        let margin = thickness;
+        let [width, height] = grid.extent(maze.width, maze.height);
+        let width = width * cell_size + 2.0 * margin;
+        let height = height * cell_size + 2.0 * margin;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <rect width="100%" height="100%" {}/>"#,
+            fill(self.back_color)
+        )
+        .unwrap();
+
+        if self.markers {
+            for (point, color) in [
+                (maze.start(), self.start_color),
+                (maze.end(), self.end_color),
+            ] {
+                if grid.is_square() {
+                    writeln!(
+                        svg,
+                        r#"  <rect x="{}" y="{}" width="{s}" height="{s}" {}/>"#,
+                        margin + point.x as f64 * cell_size,
+                        margin + point.y as f64 * cell_size,
+                        fill(color),
+                        s = cell_size
+                    )
+                    .unwrap();
+                } else {
+                    for tile in grid.tiles(&point, maze.width, maze.height) {
+                        writeln!(
+                            svg,
+                            r#"  <polygon points="{}" {}/>"#,
+                            self.points(tile.into_iter()),
+                            fill(color)
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+        }
+
+        if let Some(path) = solution.filter(|path| !path.is_empty()) {
+            writeln!(
+                svg,
+                r#"  <polyline points="{}" fill="none" {} stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                self.points(path.iter().map(|point| grid.center(point, maze.width, maze.height))),
+                stroke(self.path_color),
+                cell_size / 4.0
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            svg,
+            r#"  <g {} stroke-width="{}" stroke-linecap="square">"#,
+            stroke(self.wall_color),
+            thickness
+        )
+        .unwrap();
+        for [x1, y1, x2, y2] in wall_segments(maze) {
+            let (x1, y1) = self.to_svg_point([x1, y1]);
+            let (x2, y2) = self.to_svg_point([x2, y2]);
+            writeln!(
+                svg,
+                r#"    <line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                x1, y1, x2, y2
+            )
+            .unwrap();
+        }
+        svg.push_str("  </g>\n</svg>\n");
+        svg
+    }
+
+    fn points<I: Iterator<Item = [f64; 2]>>(&self, points: I) -> String {
+        let points: Vec<String> = points
+            .map(|point| {
+                let (x, y) = self.to_svg_point(point);
+                format!("{},{}", x, y)
+            })
+            .collect();
+        points.join(" ")
+    }
+
+    pub fn save<P: AsRef<std::path::Path>>(
+        &self,
+        maze: &Maze,
+        solution: Option<&Path>,
+        file: P,
+    ) -> io::Result<()> {
+        fs::write(file, self.render(maze, solution))
+    }
+}
+
+fn fill(color: Color) -> String {
+    format!(
+        r#"fill="{}" fill-opacity="{}""#,
+        color::to_hex(color),
+        color[3].clamp(0.0, 1.0)
+    )
+}
+
+fn stroke(color: Color) -> String {
+    format!(
+        r#"stroke="{}" stroke-opacity="{}""#,
+        color::to_hex(color),
+        color[3].clamp(0.0, 1.0)
+    )
+}
+
+// Walls in grid coordinates. On square grids, collinear neighbouring walls are merged into
+// one segment.
+fn wall_segments(maze: &Maze) -> Vec<[f64; 4]> {
+    if !maze.grid().is_square() {
+        return (0..maze.height).flat_map(|y| wall_sides(maze, y)).collect();
+    }
+    let horizontal = (0..=maze.height).flat_map(|y| {
+        horizontal_wall_runs(maze, y)
+            .into_iter()
+            .map(move |run| [run.start as f64, y as f64, run.end as f64, y as f64])
+    });
+    let vertical = (0..=maze.width).flat_map(|x| {
+        vertical_wall_runs(maze, x)
+            .into_iter()
+            .map(move |run| [x as f64, run.start as f64, x as f64, run.end as f64])
+    });
+    horizontal.chain(vertical).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::{Direction, Maze, Point};
+    use crate::svg::wall_segments;
+
+    #[test]
+    fn merged_wall_segments() {
+        let mut maze = Maze::new(2, 1);
+        maze.cell_mut(&Point { x: 0, y: 0 })
+            .remove_wall(&Direction::East);
+        maze.cell_mut(&Point { x: 1, y: 0 })
+            .remove_wall(&Direction::West);
+
+        assert_eq!(
+            wall_segments(&maze),
+            vec![
+                [0.0, 0.0, 2.0, 0.0],
+                [0.0, 1.0, 2.0, 1.0],
+                [0.0, 0.0, 0.0, 1.0],
+                [2.0, 0.0, 2.0, 1.0]
+            ]
+        );
+    }
+}
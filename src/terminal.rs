@@ -0,0 +1,243 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::generation::CellState;
+use crate::maze::{Maze, Point};
+use crate::solving::Path;
+
+type Rgb = (u8, u8, u8);
+
+const START_RGB: Rgb = (0, 255, 0);
+const END_RGB: Rgb = (255, 0, 0);
+const PATH_RGB: Rgb = (0, 122, 204);
+const VISITED_RGB: Rgb = (0, 0, 255);
+const CURRENT_RGB: Rgb = (255, 255, 0);
+const HEAT_NEAR_RGB: Rgb = (0, 0, 255);
+const HEAT_FAR_RGB: Rgb = (255, 0, 0);
+
+const RESET: &str = "\x1b[0m";
+
+pub enum Overlay<'a> {
+    None,
+    Path(&'a Path),
+    Distances(&'a HashMap<Point, usize>),
+    GeneratorState(&'a HashMap<Point, CellState>),
+}
+
+pub struct TerminalRenderer {
+    colored: bool,
+    cell_width: usize,
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerminalRenderer {
+    pub fn new() -> Self {
+        Self {
+            colored: true,
+            cell_width: 3,
+        }
+    }
+
+    pub fn colored(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    pub fn cell_width(mut self, cell_width: usize) -> Self {
+        self.cell_width = cell_width.max(1);
+        self
+    }
+
+    pub fn render(&self, maze: &Maze, overlay: &Overlay) -> String {
+        let mut output = String::new();
+        let max_distance = match overlay {
+            Overlay::Distances(distances) => distances
+                .values()
+                .filter(|&&d| d != usize::MAX)
+                .max()
+                .copied()
+                .unwrap_or(0),
+            _ => 0,
+        };
+        let path_cells: HashSet<Point> = match overlay {
+            Overlay::Path(path) => path.iter().copied().collect(),
+            _ => HashSet::new(),
+        };
+
+        for y in 0..=maze.height {
+            for x in 0..=maze.width {
+                output.push(junction(maze, x, y));
+                if x < maze.width {
+                    let segment = if maze.horizontal_wall(x, y) {
+                        '─'
+                    } else {
+                        ' '
+                    };
+                    output.extend(std::iter::repeat(segment).take(self.cell_width));
+                }
+            }
+            output.push('\n');
+
+            if y == maze.height {
+                break;
+            }
+
+            for x in 0..=maze.width {
+                output.push(if maze.vertical_wall(x, y) { '│' } else { ' ' });
+                if x < maze.width {
+                    let point = Point { x, y };
+                    let (glyph, color) =
+                        self.cell_content(maze, point, overlay, &path_cells, max_distance);
+                    self.push_cell(&mut output, glyph, color);
+                }
+            }
+            output.push('\n');
+        }
+        output
+    }
+
+    fn cell_content(
+        &self,
+        maze: &Maze,
+        point: Point,
+        overlay: &Overlay,
+        path_cells: &HashSet<Point>,
+        max_distance: usize,
+    ) -> (char, Option<Rgb>) {
+        if point == maze.start() {
+            return ('S', Some(START_RGB));
+        }
+        if point == maze.end() {
+            return ('E', Some(END_RGB));
+        }
+
+        match overlay {
+            Overlay::None => (' ', None),
+            Overlay::Path(_) => {
+                if path_cells.contains(&point) {
+                    ('•', Some(PATH_RGB))
+                } else {
+                    (' ', None)
+                }
+            }
+            Overlay::Distances(distances) => match distances.get(&point) {
+                Some(&d) if d != usize::MAX => {
+                    let ratio = if max_distance == 0 {
+                        0.0
+                    } else {
+                        d as f64 / max_distance as f64
+                    };
+                    (shade(ratio), Some(lerp(HEAT_NEAR_RGB, HEAT_FAR_RGB, ratio)))
+                }
+                _ => (' ', None),
+            },
+            Overlay::GeneratorState(states) => match states.get(&point) {
+                Some(CellState::Visited) => ('░', Some(VISITED_RGB)),
+                Some(CellState::Current) => ('@', Some(CURRENT_RGB)),
+                _ => (' ', None),
+            },
+        }
+    }
+
+    fn push_cell(&self, output: &mut String, glyph: char, color: Option<Rgb>) {
+        let padding = self.cell_width - 1;
+        let left = padding / 2;
+        let right = padding - left;
+
+        match color {
+            Some((r, g, b)) if self.colored => {
+                output.push_str(&format!("\x1b[48;2;{};{};{}m\x1b[30m", r, g, b));
+                output.extend(std::iter::repeat(' ').take(left));
+                output.push(if glyph == 'S' || glyph == 'E' {
+                    glyph
+                } else {
+                    ' '
+                });
+                output.extend(std::iter::repeat(' ').take(right));
+                output.push_str(RESET);
+            }
+            _ => {
+                output.extend(std::iter::repeat(' ').take(left));
+                output.push(glyph);
+                output.extend(std::iter::repeat(' ').take(right));
+            }
+        }
+    }
+}
+
+fn junction(maze: &Maze, x: usize, y: usize) -> char {
+    let up = y > 0 && maze.vertical_wall(x, y - 1);
+    let down = y < maze.height && maze.vertical_wall(x, y);
+    let left = x > 0 && maze.horizontal_wall(x - 1, y);
+    let right = x < maze.width && maze.horizontal_wall(x, y);
+
+    match (up, down, left, right) {
+        (false, false, false, false) => ' ',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╷',
+        (false, false, true, false) => '╴',
+        (false, false, false, true) => '╶',
+        (true, true, false, false) => '│',
+        (false, false, true, true) => '─',
+        (false, true, false, true) => '┌',
+        (false, true, true, false) => '┐',
+        (true, false, false, true) => '└',
+        (true, false, true, false) => '┘',
+        (true, true, false, true) => '├',
+        (true, true, true, false) => '┤',
+        (false, true, true, true) => '┬',
+        (true, false, true, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+fn shade(ratio: f64) -> char {
+    if ratio < 0.25 {
+        '░'
+    } else if ratio < 0.5 {
+        '▒'
+    } else if ratio < 0.75 {
+        '▓'
+    } else {
+        '█'
+    }
+}
+
+fn lerp(from: Rgb, to: Rgb, ratio: f64) -> Rgb {
+    let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * ratio).round() as u8;
+    (
+        channel(from.0, to.0),
+        channel(from.1, to.1),
+        channel(from.2, to.2),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::maze::{Direction, Maze, Point};
+    use crate::terminal::{Overlay, TerminalRenderer};
+
+    #[test]
+    fn junction_glyphs() {
+        let mut maze = Maze::new(2, 2);
+        maze.cell_mut(&Point { x: 0, y: 0 })
+            .remove_wall(&Direction::East);
+        maze.cell_mut(&Point { x: 1, y: 0 })
+            .remove_wall(&Direction::West);
+        maze.cell_mut(&Point { x: 1, y: 0 })
+            .remove_wall(&Direction::South);
+        maze.cell_mut(&Point { x: 1, y: 1 })
+            .remove_wall(&Direction::North);
+
+        let rendered = TerminalRenderer::new()
+            .colored(false)
+            .cell_width(1)
+            .render(&maze, &Overlay::None);
+
+        assert_eq!(rendered, "┌───┐\n│S  │\n├─┐ │\n│ │E│\n└─┴─┘\n");
+    }
+}
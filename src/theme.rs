@@ -0,0 +1,245 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+use crate::color::{
+    self, Color, BACK_COLOR, CURRENT_COLOR, END_COLOR, HUD_BACK_COLOR, HUD_TEXT_COLOR, PATH_COLOR,
+    PLAYER_COLOR, START_COLOR, TRAIL_COLOR, VISITED_COLOR, WALL_COLOR,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub back: Color,
+    pub wall: Color,
+    pub visited: Color,
+    pub current: Color,
+    pub path: Color,
+    pub start: Color,
+    pub end: Color,
+    pub trail: Color,
+    pub player: Color,
+    pub hud_back: Color,
+    pub hud_text: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        back: BACK_COLOR,
+        wall: WALL_COLOR,
+        visited: VISITED_COLOR,
+        current: CURRENT_COLOR,
+        path: PATH_COLOR,
+        start: START_COLOR,
+        end: END_COLOR,
+        trail: TRAIL_COLOR,
+        player: PLAYER_COLOR,
+        hud_back: HUD_BACK_COLOR,
+        hud_text: HUD_TEXT_COLOR,
+    };
+
+    pub const LIGHT: Theme = Theme {
+        back: [1.0, 1.0, 1.0, 1.0],
+        wall: [0.1, 0.1, 0.1, 1.0],
+        visited: [0.78, 0.86, 1.0, 1.0],
+        current: [1.0, 0.8, 0.2, 1.0],
+        path: [0.2, 0.4, 0.9, 1.0],
+        start: [0.2, 0.7, 0.3, 1.0],
+        end: [0.85, 0.2, 0.2, 1.0],
+        trail: [0.5, 0.8, 0.8, 1.0],
+        player: [1.0, 0.5, 0.0, 1.0],
+        hud_back: [1.0, 1.0, 1.0, 0.8],
+        hud_text: [0.0, 0.0, 0.0, 1.0],
+    };
+
+    pub const HIGH_CONTRAST: Theme = Theme {
+        back: [0.0, 0.0, 0.0, 1.0],
+        wall: [1.0, 1.0, 1.0, 1.0],
+        visited: [0.25, 0.25, 0.25, 1.0],
+        current: [1.0, 1.0, 0.0, 1.0],
+        path: [0.0, 1.0, 1.0, 1.0],
+        start: [0.0, 1.0, 0.0, 1.0],
+        end: [1.0, 0.0, 1.0, 1.0],
+        trail: [0.4, 0.4, 1.0, 1.0],
+        player: [1.0, 0.5, 0.0, 1.0],
+        hud_back: [0.0, 0.0, 0.0, 0.85],
+        hud_text: [1.0, 1.0, 1.0, 1.0],
+    };
+
+    // Okabe-Ito palette, distinguishable with the common forms of color blindness.
+    pub const COLORBLIND: Theme = Theme {
+        back: [1.0, 1.0, 1.0, 1.0],
+        wall: [0.0, 0.0, 0.0, 1.0],
+        visited: [0.337, 0.706, 0.914, 1.0],
+        current: [0.941, 0.894, 0.259, 1.0],
+        path: [0.0, 0.447, 0.698, 1.0],
+        start: [0.0, 0.62, 0.451, 1.0],
+        end: [0.835, 0.369, 0.0, 1.0],
+        trail: [0.8, 0.475, 0.655, 1.0],
+        player: [0.902, 0.624, 0.0, 1.0],
+        hud_back: [0.0, 0.0, 0.0, 0.6],
+        hud_text: [1.0, 1.0, 1.0, 1.0],
+    };
+
+    pub const PRESETS: [(&'static str, Theme); 4] = [
+        ("dark", Theme::DARK),
+        ("light", Theme::LIGHT),
+        ("high-contrast", Theme::HIGH_CONTRAST),
+        ("colorblind", Theme::COLORBLIND),
+    ];
+
+    // Theme files are `key = value` lines where values are `#rrggbb` or `#rrggbbaa` colors,
+    // lines starting with `#` are comments. A `preset = NAME` line starts over from a preset, so it
+    // usually comes first; colors that are not set keep their dark theme value.
+    pub fn parse(content: &str) -> Result<Self, ThemeError> {
+        let mut theme = Theme::DARK;
+
+        for (number, line) in content.lines().enumerate() {
+            let line_number = number + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ThemeError::Syntax(line_number))?;
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+
+            if key == "preset" {
+                theme = value
+                    .parse()
+                    .map_err(|_| ThemeError::UnknownPreset(value.to_string()))?;
+                continue;
+            }
+            let color = color::from_hex(value)
+                .ok_or_else(|| ThemeError::InvalidColor(line_number, value.to_string()))?;
+            *theme
+                .color_mut(key)
+                .ok_or_else(|| ThemeError::UnknownKey(line_number, key.to_string()))? = color;
+        }
+        Ok(theme)
+    }
+
+    pub fn load<P: AsRef<std::path::Path>>(file: P) -> Result<Self, ThemeError> {
+        let content = fs::read_to_string(file).map_err(ThemeError::Io)?;
+        Theme::parse(&content)
+    }
+
+    fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        match key {
+            "back" => Some(&mut self.back),
+            "wall" => Some(&mut self.wall),
+            "visited" => Some(&mut self.visited),
+            "current" => Some(&mut self.current),
+            "path" => Some(&mut self.path),
+            "start" => Some(&mut self.start),
+            "end" => Some(&mut self.end),
+            "trail" => Some(&mut self.trail),
+            "player" => Some(&mut self.player),
+            "hud_back" => Some(&mut self.hud_back),
+            "hud_text" => Some(&mut self.hud_text),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+// Only the preset names, see `Theme::load` for theme files.
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Theme::PRESETS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|(_, theme)| *theme)
+            .ok_or_else(|| format!("unknown theme '{}'", s))
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    Syntax(usize),
+    UnknownKey(usize, String),
+    InvalidColor(usize, String),
+    UnknownPreset(String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(error) => write!(f, "{}", error),
+            ThemeError::Syntax(line) => write!(f, "line {}: expected `key = value`", line),
+            ThemeError::UnknownKey(line, key) => {
+                write!(f, "line {}: unknown color '{}'", line, key)
+            }
+            ThemeError::InvalidColor(line, value) => write!(
+                f,
+                "line {}: invalid color '{}', expected #rrggbb or #rrggbbaa",
+                line, value
+            ),
+            ThemeError::UnknownPreset(name) => write!(f, "unknown theme preset '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::theme::{Theme, ThemeError};
+
+    #[test]
+    fn parse_theme_file() {
+        let theme = Theme::parse(
+            "# A light theme with a green path\n\
+             preset = light\n\
+             \n\
+             path = #00ff00\n\
+             hud_back = #00000080\n",
+        )
+        .unwrap();
+
+        assert_eq!(theme.back, Theme::LIGHT.back);
+        assert_eq!(theme.path, [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(theme.hud_back[3], 128.0 / 255.0);
+
+        assert!(matches!(
+            Theme::parse("walls = #000000"),
+            Err(ThemeError::UnknownKey(1, _))
+        ));
+        assert!(matches!(
+            Theme::parse("\nwall = black"),
+            Err(ThemeError::InvalidColor(2, _))
+        ));
+        assert!("sepia".parse::<Theme>().is_err());
+    }
+
+    #[test]
+    fn preset_colors_are_in_range() {
+        for (_, theme) in Theme::PRESETS.iter() {
+            let colors = [
+                theme.back,
+                theme.wall,
+                theme.visited,
+                theme.current,
+                theme.path,
+                theme.start,
+                theme.end,
+                theme.trail,
+                theme.player,
+                theme.hud_back,
+                theme.hud_text,
+            ];
+            for channel in colors.iter().flatten() {
+                assert!((0.0..=1.0).contains(channel));
+            }
+        }
+    }
+}